@@ -2,10 +2,9 @@ use std::sync::Mutex;
 
 use crate::log;
 use crate::monitor::gm;
+use crate::platform::{self, PlatformError};
 use crate::utils::types::NapiWeakThreadsafeFunction;
 
-mod foreground_hook;
-
 const CHANNEL_SIZE: usize = 64;
 
 static RX_HANDLE: Mutex<Option<tokio::task::JoinHandle<()>>> = Mutex::new(None);
@@ -13,18 +12,22 @@ static RX_HANDLE: Mutex<Option<tokio::task::JoinHandle<()>>> = Mutex::new(None);
 pub async fn install_hook(
   callback: Option<NapiWeakThreadsafeFunction<String, ()>>,
   wait_time: Option<u32>,
-) {
+) -> platform::Result<()> {
   // uninstall existing hook
   uninstall_hook().await;
 
   let (tx, rx) = tokio::sync::mpsc::channel::<u32>(CHANNEL_SIZE);
 
   // install hook
-  let result = foreground_hook::install_foreground_hook(tx);
+  let result = platform::install_foreground_hook(tx);
   if let Err(err) = result {
-    log::error(err.as_str());
+    // there is nothing to log if the platform simply doesn't have a foreground hook
+    if matches!(err, PlatformError::Unsupported(_)) {
+      return Err(err);
+    }
+    log::error(err.to_string().as_str());
     log::error("failed to install foreground hook");
-    return;
+    return Ok(());
   }
 
   // spawn receiver thread
@@ -38,11 +41,12 @@ pub async fn install_hook(
   if let Some(t) = wait_time {
     gm::get().lock().await.set_foreground_wait_time(t as u64);
   }
+  Ok(())
 }
 
 pub async fn uninstall_hook() {
   // uninstall hook
-  foreground_hook::uninstall_foreground_hook();
+  platform::uninstall_foreground_hook();
   // clear callback
   gm::get().lock().await.unset_foreground_callback();
   // clear rx_handle global variable
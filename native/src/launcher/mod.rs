@@ -0,0 +1,220 @@
+use std::{io, path::Path, process::ExitStatus, sync::Arc, time::Duration};
+
+use tokio::{process::Command, sync::oneshot};
+
+use crate::{
+  log,
+  monitor::{gm, ProcessMessage},
+  napi_launcher::{LaunchError, LaunchErrorKind, LaunchOptions},
+  platform,
+};
+
+/// `ERROR_ELEVATION_REQUIRED`, creating a process whose manifest requires administrator rights
+/// fails with it unless the caller is elevated
+#[cfg(windows)]
+const ERROR_ELEVATION_REQUIRED: i32 = 740;
+
+/// Default window of `LaunchOptions::early_exit_window`
+const DEFAULT_EARLY_EXIT_WINDOW: Duration = Duration::from_secs(2);
+/// How often the processes of a contained game are synced with its process groups
+const PROCESS_GROUP_SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+impl LaunchError {
+  fn new(kind: LaunchErrorKind, message: String) -> Self {
+    Self {
+      kind,
+      message,
+      exit_code: None,
+    }
+  }
+
+  fn from_io(path: &str, err: io::Error) -> Self {
+    #[cfg(windows)]
+    if err.raw_os_error() == Some(ERROR_ELEVATION_REQUIRED) {
+      return Self::new(
+        LaunchErrorKind::ElevationRequired,
+        format!("{} requires administrator rights: {}", path, err),
+      );
+    }
+    let kind = match err.kind() {
+      io::ErrorKind::NotFound => LaunchErrorKind::NotFound,
+      io::ErrorKind::PermissionDenied => LaunchErrorKind::PermissionDenied,
+      _ => LaunchErrorKind::SpawnFailed,
+    };
+    Self::new(kind, format!("failed to launch {}: {}", path, err))
+  }
+}
+
+/// Launch the executable of a game and track its process as the game right away, so that the game
+/// is tracked even if its path does not match the paths of the game.
+///
+/// The exit of the process is reported by the launcher as well, so that it is tracked without a
+/// running monitor. Descendants are attributed by their parents as usual, with `track_tree` the
+/// processes started before the launched process exits are tracked even if their start has been
+/// missed, e.g. between two polls.
+///
+/// With `contain`, the process and all its descendants are contained in a process group if the
+/// platform supports it. Every process in the group belongs to the game, even if it has been
+/// reparented, and the session ends once the group is empty.
+///
+/// Returns the pid, or an error if the process can not be created or exits with a failure within
+/// the early exit window.
+pub async fn launch_game(game_id: String, options: LaunchOptions) -> Result<u32, LaunchError> {
+  let path = Path::new(&options.path);
+  if !path.is_file() {
+    return Err(LaunchError::new(
+      LaunchErrorKind::NotFound,
+      format!("executable {} is not found", options.path),
+    ));
+  }
+  let mut command = Command::new(path);
+  command.args(options.args.unwrap_or_default());
+  // games usually load their assets relative to their own folder
+  let cwd = options
+    .cwd
+    .as_deref()
+    .map(Path::new)
+    .or_else(|| path.parent())
+    .filter(|cwd| !cwd.as_os_str().is_empty());
+  if let Some(cwd) = cwd {
+    command.current_dir(cwd);
+  }
+  if let Some(env) = options.env {
+    command.envs(env);
+  }
+  let group = if options.contain.unwrap_or(true) {
+    platform::prepare_process_group(&mut command, &game_id)
+      .inspect_err(|e| {
+        log::info(format!("game {} is launched without containment: {}", game_id, e).as_str())
+      })
+      .ok()
+  } else {
+    None
+  };
+  let mut child = command
+    .spawn()
+    .map_err(|err| LaunchError::from_io(&options.path, err))?;
+  // attach right away, the process may be suspended until then
+  let group = group
+    .zip(child.id())
+    .and_then(|(group, pid)| match group.attach(pid) {
+      Ok(()) => Some(Arc::<dyn platform::ProcessGroup>::from(group)),
+      Err(e) => {
+        log::error(
+          format!(
+            "failed to contain process {} of game {}: {}",
+            pid, game_id, e
+          )
+          .as_str(),
+        );
+        None
+      }
+    });
+  let Some(pid) = child.id() else {
+    return Err(LaunchError::new(
+      LaunchErrorKind::SpawnFailed,
+      format!("process of {} exited before it is tracked", options.path),
+    ));
+  };
+  log::info(
+    format!(
+      "game launched: {}, pid: {}, path: {}",
+      game_id, pid, options.path
+    )
+    .as_str(),
+  );
+
+  let track_tree = options.track_tree.unwrap_or(false);
+  let is_contained = group.is_some();
+  let start_time = platform::get_process_start_time(pid);
+  let mut msg = ProcessMessage::started(pid, std::process::id(), options.path.clone());
+  if let Some(start_time) = start_time {
+    msg = msg.with_start_time(start_time);
+  }
+  let mut gm = gm::get().lock().await;
+  gm.track_launched_process(&game_id, msg);
+  if let Some(group) = group {
+    if gm.add_process_group(&game_id, group) {
+      tokio::spawn(sync_process_groups(game_id.clone()));
+    }
+  }
+  drop(gm);
+
+  let (exit_tx, exit_rx) = oneshot::channel();
+  let path = options.path.clone();
+  tokio::spawn(async move {
+    let exit_code = match child.wait().await {
+      Ok(status) => exit_code(status),
+      Err(e) => {
+        log::error(format!("failed to wait for launched process {}: {}", pid, e).as_str());
+        None
+      }
+    };
+    let mut msg = ProcessMessage::terminated(pid, path);
+    if let Some(start_time) = start_time {
+      msg = msg.with_start_time(start_time);
+    }
+    if let Some(exit_code) = exit_code {
+      msg = msg.with_exit_code(exit_code);
+    }
+    let mut gm = gm::get().lock().await;
+    // children are reparented once their parent exits, collect them while they are still linked,
+    // contained children are tracked as members of the group instead
+    if track_tree && !is_contained {
+      gm.track_descendants(&game_id, pid, start_time, platform::get_all_process());
+    }
+    gm.handle_process_message(msg);
+    drop(gm);
+    let _ = exit_tx.send(exit_code);
+  });
+
+  let window = options
+    .early_exit_window
+    .map_or(DEFAULT_EARLY_EXIT_WINDOW, |ms| {
+      Duration::from_millis(ms.into())
+    });
+  if window.is_zero() {
+    return Ok(pid);
+  }
+  match tokio::time::timeout(window, exit_rx).await {
+    Ok(Ok(Some(exit_code))) if exit_code != 0 => Err(LaunchError {
+      kind: LaunchErrorKind::EarlyExit,
+      message: format!(
+        "{} exited with code {} right after launch",
+        options.path, exit_code
+      ),
+      exit_code: Some(exit_code),
+    }),
+    // a launcher handing over to the game exits successfully
+    _ => Ok(pid),
+  }
+}
+
+/// Keep the processes of a contained game in sync with its process groups until they are empty, so
+/// that processes are tracked and their exits are reported without a monitor
+async fn sync_process_groups(game_id: String) {
+  let mut interval = tokio::time::interval(PROCESS_GROUP_SYNC_INTERVAL);
+  loop {
+    interval.tick().await;
+    if !gm::get().lock().await.sync_process_groups(&game_id) {
+      break;
+    }
+  }
+}
+
+/// Exit code of an exited process, a process killed by a signal exits with 128 + the signal as
+/// shells report it
+fn exit_code(status: ExitStatus) -> Option<i64> {
+  #[cfg(unix)]
+  {
+    use std::os::unix::process::ExitStatusExt;
+    if let Some(signal) = status.signal() {
+      return Some(128 + i64::from(signal));
+    }
+  }
+  // exit codes are unsigned on Windows, e.g. 0xC0000005 of an access violation
+  #[cfg(windows)]
+  return status.code().map(|code| i64::from(code as u32));
+  #[cfg(not(windows))]
+  status.code().map(i64::from)
+}
@@ -1,14 +1,18 @@
 mod log;
 mod utils;
 mod runtime;
+mod platform;
+#[cfg(windows)]
 mod win32;
 mod monitor;
 mod foreground;
+mod launcher;
 
 pub mod napi_log;
 pub mod napi_win32;
 pub mod napi_monitor;
 pub mod napi_foreground;
+pub mod napi_launcher;
 
 #[cfg(test)]
 mod tests;
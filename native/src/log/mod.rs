@@ -18,6 +18,7 @@ struct Logger {
 
 static LOGGER: OnceLock<Logger> = OnceLock::new();
 
+#[allow(clippy::redundant_field_names)]
 pub async fn init_logger(
   fn_info: NapiWeakThreadsafeFunction<String, ()>,
   fn_err: NapiWeakThreadsafeFunction<String, ()>,
@@ -2,11 +2,12 @@ use std::{ffi::c_void, mem};
 use tokio::sync::mpsc;
 use windows::{
   core::*,
-  Win32::{Foundation, System::Diagnostics::Etw, System::Time},
+  Win32::{Foundation, Globalization, System::Diagnostics::Etw, System::Time},
 };
 
-use crate::log;
-use crate::monitor::{ProcessMessage, ProcessStatus};
+use crate::monitor::ProcessMessage;
+use crate::utils::etw_event::{self, ProcessStart, ProcessStop};
+use crate::{log, win32};
 
 // Private session GUID (just wrote down a random GUID, can be changed at will)
 const SESSION_GUID: GUID = GUID::from_u128(0xae44cb98_bd11_4069_10be_7dea1390e2ff);
@@ -377,75 +378,55 @@ impl Drop for EtwTrace {
 
 struct DecoderContext {
   tx: mpsc::Sender<ProcessMessage>,
+  /// ANSI code page of the system, which ANSI strings of events are encoded with
+  code_page: u16,
 }
 
 impl DecoderContext {
   pub fn new(tx: mpsc::Sender<ProcessMessage>) -> Self {
-    Self { tx }
+    Self {
+      tx,
+      code_page: unsafe { Globalization::GetACP() } as u16,
+    }
   }
 
   pub fn process_event_record(&self, record: &Etw::EVENT_RECORD) {
+    if record.UserData.is_null() || record.UserDataLength == 0 {
+      return;
+    }
+    let data = unsafe {
+      std::slice::from_raw_parts(record.UserData as *const u8, record.UserDataLength as usize)
+    };
+    let version = record.EventHeader.EventDescriptor.Version;
     match record.EventHeader.EventDescriptor.Id {
-      PROCESS_START_EVENT_ID => {
-        if record.UserDataLength > 0 {
-          unsafe { self.handle_process_start(record) };
-        }
-      }
+      PROCESS_START_EVENT_ID => match etw_event::decode_process_start(version, data) {
+        Ok(event) => self.handle_process_start(event),
+        Err(e) => log::error(format!("failed to decode a process start event: {}", e).as_str()),
+      },
       PROCESS_STOP_EVENT_ID => {
-        if record.UserDataLength > 0 {
-          unsafe { self.handle_process_stop(record) };
+        match etw_event::decode_process_stop(version, data, self.code_page) {
+          Ok(event) => self.handle_process_stop(event),
+          Err(e) => log::error(format!("failed to decode a process stop event: {}", e).as_str()),
         }
       }
       _ => {} // Ignore other events
     }
   }
 
-  // Using manual parsing to retrieve event data
-  // The process stop user data schema is defined as following
-  //
-  // <template tid="ProcessStopArgs_V2">
-  //   <data name="ProcessID" inType="win:UInt32" />
-  //   <data name="ProcessSequenceNumber" inType="win:UInt64" />
-  //   <data name="CreateTime" inType="win:FILETIME" />
-  //   <data name="ExitTime" inType="win:FILETIME" />
-  //   <data name="ExitCode" inType="win:UInt32" />
-  //   <data name="TokenElevationType" inType="win:UInt32" />
-  //   <data name="HandleCount" inType="win:UInt32" />
-  //   <data name="CommitCharge" inType="win:UInt64" />
-  //   <data name="CommitPeak" inType="win:UInt64" />
-  //   <data name="CPUCycleCount" inType="win:UInt64" />
-  //   <data name="ReadOperationCount" inType="win:UInt32" />
-  //   <data name="WriteOperationCount" inType="win:UInt32" />
-  //   <data name="ReadTransferKiloBytes" inType="win:UInt32" />
-  //   <data name="WriteTransferKiloBytes" inType="win:UInt32" />
-  //   <data name="HardFaultCount" inType="win:UInt32" />
-  //   <data name="ImageName" inType="win:AnsiString" />
-  // </template>
-  unsafe fn handle_process_stop(&self, record: &Etw::EVENT_RECORD) {
-    if record.UserData.is_null() || record.UserDataLength == 0 {
-      return;
-    }
-
-    let data_ptr = record.UserData as *const u8;
-    let data_length = record.UserDataLength as usize;
-
-    // Parse the structure according to ProcessStopArgs_V2 template
-    if 4 > data_length {
-      log::error("buffer overflow while reading data");
-      return;
-    }
-    let process_id = (data_ptr as *const u32).read_unaligned();
-
-    match self.tx.try_send(ProcessMessage {
-      pid: process_id,
-      status: ProcessStatus::Terminated,
-      path: String::new(),
-    }) {
+  fn handle_process_stop(&self, event: ProcessStop) {
+    // the create time rather than the sequence number identifies the process, since processes
+    // found by the startup scan have no sequence number
+    let resources = event.resources();
+    let msg = ProcessMessage::terminated(event.process_id, event.image_name)
+      .with_start_time(win32::filetime_to_unix_millis(event.create_time))
+      .with_exit_code(event.exit_code.into())
+      .with_resources(resources);
+    match self.tx.try_send(msg) {
       Err(e) => {
         log::error(
           format!(
             "failed to send a process stop information, pid: {}: {:?}",
-            process_id, e
+            event.process_id, e
           )
           .as_str(),
         );
@@ -454,129 +435,26 @@ impl DecoderContext {
     }
   }
 
-  // Using manual parsing to retrieve event data
-  // The process start user data schema is defined as following
-  //
-  // <template tid="ProcessStartArgs_V4">
-  //   <data name="ProcessID" inType="win:UInt32" />
-  //   <data name="ProcessSequenceNumber" inType="win:UInt64" />
-  //   <data name="CreateTime" inType="win:FILETIME" />
-  //   <data name="ParentProcessID" inType="win:UInt32" />
-  //   <data name="ParentProcessSequenceNumber" inType="win:UInt64" />
-  //   <data name="SessionID" inType="win:UInt32" />
-  //   <data name="Flags" inType="win:UInt32" />
-  //   <data name="ProcessTokenElevationType" inType="win:UInt32" />
-  //   <data name="ProcessTokenIsElevated" inType="win:UInt32" />
-  //   <data name="MandatoryLabel" inType="win:SID" />
-  //   <data name="ImageName" inType="win:UnicodeString" />
-  //   <data name="ImageChecksum" inType="win:UInt32" />
-  //   <data name="TimeDateStamp" inType="win:UInt32" />
-  //   <data name="PackageFullName" inType="win:UnicodeString" />
-  //   <data name="PackageRelativeAppId" inType="win:UnicodeString" />
-  //   <data name="SecurityMitigations" inType="win:UInt32" />
-  // </template>
-  unsafe fn handle_process_start(&self, record: &Etw::EVENT_RECORD) {
-    if record.UserData.is_null() || record.UserDataLength == 0 {
-      return;
-    }
-
-    let data_ptr = record.UserData as *const u8;
-    let data_length = record.UserDataLength as usize;
-    let mut offset = 0usize;
-
-    // Helper macro to safely read data
-    macro_rules! read_value {
-      ($type:ty) => {{
-        if offset + mem::size_of::<$type>() > data_length {
-          log::error("Buffer overflow while reading data");
-          return;
-        }
-        let value = (data_ptr.add(offset) as *const $type).read_unaligned();
-        offset += mem::size_of::<$type>();
-        value
-      }};
-    }
-
-    // Parse the structure according to template schema
-    let process_id = read_value!(u32);
-    // let process_seq = read_value!(u64);
-    offset += 8;
-    // let create_time = read_value!(FILETIME);
-    // let parent_pid = read_value!(u32);
-    // let parent_seq = read_value!(u64);
-    // let session_id = read_value!(u32);
-    // let flags = read_value!(u32);
-    // let token_elevation_type = read_value!(u32);
-    // let token_is_elevated = read_value!(u32);
-    offset += 8 + 4 + 8 + 4 + 4 + 4 + 4;
-
-    // Skip MandatoryLabel (SID) - variable length
-    // SID structure starts with Revision (1 byte) and SubAuthorityCount (1 byte)
-    if offset + 2 <= data_length {
-      let sub_authority_count = *(data_ptr.add(offset + 1));
-      let sid_size = 8 + (sub_authority_count as usize * 4); // 8 bytes header + 4 bytes per SubAuthority
-      offset += sid_size;
-    } else {
-      log::error("failed to parse SID");
-      return;
-    }
-
-    // read ImageName (null-terminated wide string)
-    let image_name = Self::read_wide_string_from_offset(data_ptr, data_length, &mut offset);
-
-    if let Some(path) = image_name {
-      // log::info(format!("Process Full Path: {}", path).as_str());
-      // send process message
-      match self.tx.try_send(ProcessMessage {
-        pid: process_id,
-        status: ProcessStatus::Started,
-        path: path,
-      }) {
-        Err(e) => {
-          log::error(
-            format!(
-              "failed to send a process start information, pid: {}: {:?}",
-              process_id, e
-            )
-            .as_str(),
-          );
-        }
-        Ok(_) => {}
-      }
-    } else {
+  fn handle_process_start(&self, event: ProcessStart) {
+    if event.image_name.is_empty() {
       log::error("failed to parse ImageName");
+      return;
     }
-  }
-
-  // Helper function to read a null-terminated wide string from a buffer at a specific offset
-  unsafe fn read_wide_string_from_offset(
-    data_ptr: *const u8,
-    data_length: usize,
-    offset: &mut usize,
-  ) -> Option<String> {
-    if *offset >= data_length {
-      return None;
-    }
-
-    let start_ptr = data_ptr.add(*offset) as *const u16;
-    let mut len = 0;
-
-    // Find null terminator
-    while *offset + (len * 2) + 2 <= data_length {
-      if *start_ptr.add(len) == 0 {
-        break;
+    // send process message
+    let msg = ProcessMessage::started(event.process_id, event.parent_process_id, event.image_name)
+      .with_start_time(win32::filetime_to_unix_millis(event.create_time));
+    match self.tx.try_send(msg) {
+      Err(e) => {
+        log::error(
+          format!(
+            "failed to send a process start information, pid: {}: {:?}",
+            event.process_id, e
+          )
+          .as_str(),
+        );
       }
-      len += 1;
-    }
-
-    if len == 0 {
-      return None;
+      Ok(_) => {}
     }
-
-    let slice = std::slice::from_raw_parts(start_ptr, len);
-    *offset += (len + 1) * 2; // Move past the string and null terminator
-
-    Some(String::from_utf16_lossy(slice))
   }
 
   #[allow(dead_code)]
@@ -1,6 +1,6 @@
 use tokio::{sync::mpsc, task};
 
-use super::{gm, ProcessMessage, WinProcessMonitor};
+use super::{gm, ProcessMessage, ProcessStatus, WinProcessMonitor};
 use crate::{log, win32};
 
 mod etw_trace;
@@ -30,12 +30,14 @@ impl EtwMonitor {
         op_data = rx.recv() => {
           if let Some(mut data) = op_data {
             // log::info(format!("[FROM RX] pid: {}, path: {}, type: {:?}", data.pid, data.path, data.status).as_str());
-            let dos_path = win32::nt_to_dos_path(&data.path);
-            match dos_path {
-              Some(path) => {
+            // a path below a `subst` drive is also matched with the path of its target,
+            // stopped processes only report their image file name, which is kept as it is
+            if data.status == ProcessStatus::Started {
+              let mut dos_paths = win32::nt_to_dos_paths(&data.path).into_iter();
+              if let Some(path) = dos_paths.next() {
                 data.path = path;
+                data.aliases.extend(dos_paths);
               }
-              None => { }
             }
             gm::get().lock().await.handle_process_message(data);
           } else {
@@ -53,7 +55,7 @@ impl EtwMonitor {
 }
 
 impl WinProcessMonitor for EtwMonitor {
-  fn start_monitoring(&mut self) -> windows_core::Result<()> {
+  fn start_monitoring(&mut self) -> std::io::Result<()> {
     log::info("ETW monitor is starting...");
     // stop existing trace session
     self.stop_monitoring();
@@ -1,40 +1,160 @@
 use napi::threadsafe_function::ThreadsafeFunctionCallMode;
-use regex::Regex;
-use std::{collections::HashMap, sync::LazyLock};
+use std::{
+  collections::HashMap,
+  sync::{Arc, LazyLock},
+  time::Duration,
+};
 use tokio::sync::Mutex;
 
 use crate::{
   log,
-  monitor::{ProcessMessage, ProcessStatus},
-  napi_monitor::{ProcessEvent, ProcessEventType},
-  utils::{path::normalize_os_path, types::NapiWeakThreadsafeFunction},
-  win32,
+  monitor::{
+    known_games::{KnownGameMatch, KnownGames},
+    rules::CompiledRule,
+    session::{GameSession, PendingEnd},
+    ProcessIdentity, ProcessMessage, ProcessStatus,
+  },
+  napi_monitor::{
+    KnownGame, KnownGameConflict, KnownGameError, KnownGameMatchMode, KnownGamePatch,
+    KnownGameReattribution, KnownGameUpdate, ProcessEvent, ProcessEventType, SessionSummary,
+  },
+  platform::{
+    self, ProcessDetails, ProcessEntry, ProcessExitHandle, ProcessGroup, ProcessImage,
+    ProcessResources,
+  },
+  utils::{
+    path::NormalizedPath,
+    steam::{self, SteamLibraries},
+    types::NapiWeakThreadsafeFunction,
+  },
 };
 
 /// Threadsafe NodeJS callback get invoked when a foreground window is changed
 static FOREGROUND_CALLBACK: std::sync::Mutex<Option<NapiWeakThreadsafeFunction<String, ()>>> =
   std::sync::Mutex::new(None);
 
-struct KnownGameProcessInfo {
+/// A game exiting with a non-zero code this soon after its session started is considered crashed,
+/// e.g. because of a missing locale or runtime, rather than quit by the player
+const CRASH_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub(crate) struct KnownGameProcessInfo {
   pid: u32,
+  /// Parent process PID, 0 if it is unknown
+  ppid: u32,
   status: ProcessStatus,
-  path: String,
+  path: NormalizedPath,
+  /// Paths the process was attributed with, kept to attribute it again when known games change
+  image: ProcessImage,
+  game_id: String,
+  details: ProcessDetails,
+  /// Exit code once the process terminated, `None` if it is running or unknown
+  exit_code: Option<i64>,
+  /// Reads the exit code if the monitor does not report it, `None` if it is not supported
+  exit_handle: Option<Arc<dyn ProcessExitHandle>>,
+  /// Resources consumed so far, sampled while running unless the monitor reports them on exit
+  resources: Option<ProcessResources>,
+  /// Launched for its game, it keeps its game whatever paths known games have
+  is_launched: bool,
+  /// Tracked as a member of a process group of its game, it has exited once it left the group
+  is_contained: bool,
+}
+
+impl KnownGameProcessInfo {
+  fn identity(&self) -> ProcessIdentity {
+    ProcessIdentity::new(self.pid, self.details.start_time)
+  }
+
+  /// The start message the process was tracked with
+  fn start_message(&self) -> ProcessMessage {
+    ProcessMessage {
+      pid: self.pid,
+      ppid: self.ppid,
+      status: ProcessStatus::Started,
+      path: self.image.path.clone(),
+      aliases: self.image.aliases.clone(),
+      details: self.details.clone(),
+      exit_code: None,
+      resources: None,
+    }
+  }
+
+  /// Merge a new sample of the resources, unless the pid already belongs to another process
+  fn sample_resources(&mut self) {
+    let is_same_process = self
+      .details
+      .start_time
+      .is_none_or(|start_time| platform::get_process_start_time(self.pid) == Some(start_time));
+    if !is_same_process {
+      return;
+    }
+    if let Some(sample) = platform::get_process_resources(self.pid) {
+      self.resources.get_or_insert_default().update(&sample);
+    }
+  }
+
+  /// Record how the process exited, what the monitor reports is preferred to what is read from the
+  /// system, which is only possible while the exited process has not been released yet
+  fn record_exit(&mut self, exit_code: Option<i64>, resources: Option<ProcessResources>) {
+    self.exit_code = exit_code.or_else(|| self.exit_handle.as_ref()?.exit_code());
+    self.exit_handle = None;
+    match resources {
+      Some(resources) => self.resources = Some(resources),
+      None => self.sample_resources(),
+    }
+  }
+}
+
+/// A process attributed to a known game which has not lived long enough yet,
+/// see `CompiledRule::min_lifetime`
+struct PendingStart {
+  l_path: NormalizedPath,
   game_id: String,
+  msg: ProcessMessage,
+  timer: tokio::task::JoinHandle<()>,
 }
 
 pub struct GameManager {
-  /// A full list of local games (path - game_id pair).
+  /// A full list of local games (path - game_ids pair).
   ///
-  /// May contains 3 kinds of game process information (file, folder, executable name)
-  known_games: HashMap<String, String>,
+  /// May contains 3 kinds of game process information (file, folder, executable name).
+  /// A key usually belongs to a single game, but different games may ship the same executable name,
+  /// e.g. `game.exe` of RPG Maker games.
+  known_games: KnownGames,
+
+  /// Definitions of the enabled known games (game_id - game pair), so that changes can be applied
+  /// as a diff
+  known_game_defs: HashMap<String, KnownGame>,
 
   /// All currently running known game processes.
-  /// The key is always "{full_path}-{pid}" of the process, not the folder or executable name.
+  /// The key is the identity of the process, so that a process reusing the pid of an exited game
+  /// process is not mistaken for it.
   ///
-  /// Only processes contained in `known_games` will be inserted into this hashmap.
+  /// Only processes contained in `known_games` and their descendants will be inserted into this hashmap.
   /// When a running process is terminated, it is designed to be remove from this hashmap.
   /// Typically there is only 1 entry stored in this hashmap, unless a user is playing 2 or more games simultaneously.
-  running_process: HashMap<String, KnownGameProcessInfo>,
+  running_process: HashMap<ProcessIdentity, KnownGameProcessInfo>,
+
+  /// Matching rules of known games, in the order they were set
+  rules: Vec<CompiledRule>,
+
+  /// Whether known game paths are also registered with symlinks and junctions resolved
+  resolve_symlinks: bool,
+
+  /// Processes waiting for the minimum lifetime of their game (pid - pending start pair)
+  pending_starts: HashMap<u32, PendingStart>,
+
+  /// Running sessions of known games (game_id - session pair)
+  sessions: HashMap<String, GameSession>,
+
+  /// Groups containing the processes of launched games (game_id - groups pair). A game with groups
+  /// runs until its groups are empty, see `sync_process_groups`.
+  process_groups: HashMap<String, Vec<Arc<dyn ProcessGroup>>>,
+
+  /// How long a session waits for the game to restart after its last process exited.
+  /// Games without an entry in `grace_periods` use `default_grace_period`.
+  grace_periods: HashMap<String, Duration>,
+  default_grace_period: Duration,
 
   /// Threadsafe NodeJS callback get invoked when a known process get created or terminated
   process_callback: Option<NapiWeakThreadsafeFunction<ProcessEvent, ()>>,
@@ -45,11 +165,25 @@ pub struct GameManager {
   foreground_timeout_handle: Option<tokio::task::JoinHandle<()>>,
 }
 
+impl PendingStart {
+  fn identity(&self, pid: u32) -> ProcessIdentity {
+    ProcessIdentity::new(pid, self.msg.details.start_time)
+  }
+}
+
 impl GameManager {
-  fn new() -> Self {
+  pub(crate) fn new() -> Self {
     Self {
-      known_games: HashMap::new(),
+      known_games: KnownGames::default(),
+      known_game_defs: HashMap::new(),
       running_process: HashMap::new(),
+      rules: Vec::new(),
+      resolve_symlinks: true,
+      pending_starts: HashMap::new(),
+      sessions: HashMap::new(),
+      process_groups: HashMap::new(),
+      grace_periods: HashMap::new(),
+      default_grace_period: Duration::ZERO,
       process_callback: None,
       foreground_pid: 0,
       foreground_wait_time: 10,
@@ -59,23 +193,277 @@ impl GameManager {
 
   pub fn init(
     &mut self,
-    pathes: Vec<String>,
-    ids: Vec<String>,
+    games: Vec<KnownGame>,
     callback: Option<NapiWeakThreadsafeFunction<ProcessEvent, ()>>,
-  ) {
-    self.init_known_games(pathes, ids);
+  ) -> KnownGameUpdate {
     self.set_process_callback(callback);
+    self.replace_known_games(games)
   }
 
-  pub fn init_known_games(&mut self, pathes: Vec<String>, ids: Vec<String>) {
-    self.known_games.clear();
-    // allocate enough memory in advance to avoid reallocation
-    let size = pathes.len();
-    self.known_games.reserve(size);
-    for (path, id) in pathes.into_iter().zip(ids.into_iter()) {
-      let l_path = normalize_os_path(path);
-      self.known_games.insert(l_path, id);
+  /// Replace all known games, games which are not given or are invalid are removed.
+  ///
+  /// Only the differences to the current games are applied, see `apply_known_games`. Returns every
+  /// key shared by several games.
+  pub fn replace_known_games(&mut self, games: Vec<KnownGame>) -> KnownGameUpdate {
+    let mut update = self.apply_known_games(games, Vec::new(), true);
+    update.conflicts = self.get_conflicts(|_| true);
+    update
+  }
+
+  /// Add or replace and remove known games, an id given in both is added.
+  ///
+  /// Returns the conflicts of the keys of the added and updated games.
+  pub fn patch_known_games(&mut self, patch: KnownGamePatch) -> KnownGameUpdate {
+    self.apply_known_games(
+      patch.upserts.unwrap_or_default(),
+      patch.removes.unwrap_or_default(),
+      false,
+    )
+  }
+
+  /// Apply changes of known games and attribute running processes again.
+  ///
+  /// Invalid games are rejected one by one, the others are compared with their current
+  /// definitions, so that unchanged games keep their keys. Disabled games are removed. If
+  /// `is_replacing`, all games which are not applied are removed as well.
+  fn apply_known_games(
+    &mut self,
+    upserts: Vec<KnownGame>,
+    removes: Vec<String>,
+    is_replacing: bool,
+  ) -> KnownGameUpdate {
+    let mut update = KnownGameUpdate::default();
+    // the requested definition of each game, `None` to remove it
+    let mut requested: Vec<(String, Option<KnownGame>)> =
+      removes.into_iter().map(|game_id| (game_id, None)).collect();
+    let mut seen_ids: HashMap<String, usize> = HashMap::with_capacity(upserts.len());
+    for (index, game) in upserts.into_iter().enumerate() {
+      let reason = match seen_ids.get(&game.id) {
+        Some(first) => Err(format!("duplicate id, first given at index {}", first)),
+        None => validate_known_game(&game),
+      };
+      if let Err(reason) = reason {
+        update.errors.push(known_game_error(&game, index, reason));
+        continue;
+      }
+      seen_ids.insert(game.id.clone(), index);
+      requested.retain(|(game_id, _)| *game_id != game.id);
+      let game_id = game.id.clone();
+      requested.push((game_id, game.enabled.unwrap_or(true).then_some(game)));
+    }
+    if is_replacing {
+      let mut omitted: Vec<String> = self
+        .known_game_defs
+        .keys()
+        .filter(|game_id| !seen_ids.contains_key(*game_id))
+        .cloned()
+        .collect();
+      omitted.sort();
+      requested.extend(omitted.into_iter().map(|game_id| (game_id, None)));
+    }
+
+    let mut libraries = None;
+    let mut keys = Vec::new();
+    for (game_id, game) in requested {
+      match (self.known_game_defs.get(&game_id), game) {
+        (None, None) => continue,
+        (Some(current), Some(game)) if *current == game => continue,
+        (None, Some(game)) => {
+          update.added.push(game_id.clone());
+          keys.extend(self.insert_known_game(game.clone(), &mut libraries));
+          self.known_game_defs.insert(game_id, game);
+        }
+        (Some(_), Some(game)) => {
+          update.updated.push(game_id.clone());
+          self.known_games.remove_game(&game_id);
+          keys.extend(self.insert_known_game(game.clone(), &mut libraries));
+          self.known_game_defs.insert(game_id, game);
+        }
+        (Some(_), None) => {
+          self.known_games.remove_game(&game_id);
+          self.known_game_defs.remove(&game_id);
+          update.removed.push(game_id);
+        }
+      }
     }
+    if update.added.is_empty() && update.updated.is_empty() && update.removed.is_empty() {
+      return update;
+    }
+    update.reattributed = self.reattribute_processes();
+    for game_id in update.removed.iter() {
+      self.end_ending_session(game_id);
+    }
+    update.conflicts = self.get_conflicts(|key| keys.iter().any(|k| k.as_str() == key));
+    update
+  }
+
+  /// Attribute all tracked processes again after known games changed, parents before their
+  /// children so that descendants follow their parents. Launched processes keep their game.
+  ///
+  /// A process which no longer belongs to its game is detached from it, then tracked as a new
+  /// process of the game it belongs to now, if any. Returns the processes which changed their game.
+  fn reattribute_processes(&mut self) -> Vec<KnownGameReattribution> {
+    let mut tracked: Vec<(ProcessIdentity, i64)> = self
+      .running_process
+      .iter()
+      .filter(|(_, info)| info.status == ProcessStatus::Started && !info.is_launched)
+      .map(|(key, info)| (*key, info.details.start_time.unwrap_or_default()))
+      .collect();
+    tracked.sort_by_key(|(key, start_time)| (*start_time, key.pid));
+    let mut reattributed = Vec::new();
+    for (key, _) in tracked {
+      let Some(info) = self.running_process.get(&key) else {
+        continue;
+      };
+      let msg = info.start_message();
+      let attributed = self.attribute_process(&msg, Some(&info.game_id));
+      if attributed
+        .as_ref()
+        .is_some_and(|(game_id, _)| *game_id == info.game_id)
+      {
+        continue;
+      }
+      let Some(info) = self.running_process.remove(&key) else {
+        continue;
+      };
+      reattributed.push(KnownGameReattribution {
+        pid: info.pid,
+        previous_id: Some(info.game_id.clone()),
+        id: attributed.as_ref().map(|(game_id, _)| game_id.clone()),
+      });
+      self.detach_game_process(info);
+      if let Some((game_id, l_path)) = attributed {
+        self.start_game_process(msg, l_path, game_id);
+      }
+    }
+
+    // pending processes have not been reported yet, they are silently moved
+    let pending: Vec<u32> = self.pending_starts.keys().copied().collect();
+    for pid in pending {
+      let Some(pending) = self.pending_starts.get(&pid) else {
+        continue;
+      };
+      let attributed = self.attribute_process(&pending.msg, Some(&pending.game_id));
+      if attributed
+        .as_ref()
+        .is_some_and(|(game_id, _)| *game_id == pending.game_id)
+      {
+        continue;
+      }
+      let Some(pending) = self.pending_starts.remove(&pid) else {
+        continue;
+      };
+      pending.timer.abort();
+      if let Some((game_id, l_path)) = attributed {
+        self.start_game_process(pending.msg, l_path, game_id);
+      }
+    }
+    reattributed
+  }
+
+  /// Track running processes which are not tracked yet, e.g. at startup or after known games
+  /// changed, parents before their children. Returns the processes which are tracked now.
+  pub fn track_running_processes(
+    &mut self,
+    mut processes: Vec<ProcessEntry>,
+  ) -> Vec<KnownGameReattribution> {
+    processes.sort_by_key(|process| (process.start_time, process.pid));
+    let mut tracked = Vec::new();
+    for process in processes {
+      let msg = ProcessMessage::started(process.pid, process.ppid, process.path)
+        .with_start_time(process.start_time);
+      let identity = msg.identity();
+      if self.find_running(&identity).is_some() {
+        continue;
+      }
+      self.handle_process_message(msg);
+      let game_id = self
+        .find_running(&identity)
+        .and_then(|key| self.running_process.get(&key))
+        .map(|info| info.game_id.clone());
+      if let Some(game_id) = game_id {
+        tracked.push(KnownGameReattribution {
+          pid: process.pid,
+          previous_id: None,
+          id: Some(game_id),
+        });
+      }
+    }
+    tracked
+  }
+
+  /// Insert a known game with each of its paths, a Steam AppId key is also registered with its
+  /// install directory.
+  ///
+  /// Steam libraries are discovered on first use only, `libraries` caches them across calls.
+  /// Paths reached through a symlink are registered with their target as well, since processes
+  /// report their resolved image path.
+  /// Returns the keys the game is registered with.
+  fn insert_known_game(
+    &mut self,
+    game: KnownGame,
+    libraries: &mut Option<SteamLibraries>,
+  ) -> Vec<NormalizedPath> {
+    let mut keys = Vec::with_capacity(game.paths.len() + 1);
+    for path in game.paths {
+      let mut path_keys = Vec::with_capacity(2);
+      if let Some(app_id) = steam::parse_app_id_key(&path) {
+        let install_dir = libraries
+          .get_or_insert_with(SteamLibraries::discover)
+          .find_install_dir(app_id);
+        match install_dir {
+          Some(dir) => path_keys.push((
+            NormalizedPath::new(dir.to_string_lossy()),
+            Some(KnownGameMatchMode::FolderRecursive),
+          )),
+          None => log::info(
+            format!(
+              "install directory of steam app {} not found, it is tracked by AppId only",
+              app_id
+            )
+            .as_str(),
+          ),
+        }
+      }
+      path_keys.push((NormalizedPath::new(&path), game.match_mode));
+      if self.resolve_symlinks {
+        let resolved: Vec<_> = path_keys
+          .iter()
+          .filter_map(|(key, mode)| {
+            let resolved = key.resolve_symlinks().filter(|resolved| resolved != key)?;
+            Some((resolved, *mode))
+          })
+          .collect();
+        path_keys.extend(resolved);
+      }
+      for (key, mode) in path_keys {
+        self.known_games.insert(key.clone(), game.id.clone(), mode);
+        keys.push(key);
+      }
+    }
+    keys
+  }
+
+  /// Get the keys which are shared by several games, sorted by key
+  fn get_conflicts(&self, filter: impl Fn(&str) -> bool) -> Vec<KnownGameConflict> {
+    let conflicts: Vec<KnownGameConflict> = self
+      .known_games
+      .get_shared_keys()
+      .into_iter()
+      .filter(|(key, _)| filter(key))
+      .map(|(key, game_ids)| KnownGameConflict { key, game_ids })
+      .collect();
+    for conflict in conflicts.iter() {
+      log::info(
+        format!(
+          "known game key {} is shared by games: {}",
+          conflict.key,
+          conflict.game_ids.join(", ")
+        )
+        .as_str(),
+      );
+    }
+    conflicts
   }
 
   pub fn set_foreground_callback(&self, callback: Option<NapiWeakThreadsafeFunction<String, ()>>) {
@@ -94,6 +482,49 @@ impl GameManager {
     self.foreground_wait_time = wait_time;
   }
 
+  /// Set the grace period of a game, or the default one if `game_id` is `None`
+  pub fn set_grace_period(&mut self, game_id: Option<String>, grace_period: Duration) {
+    match game_id {
+      Some(id) => {
+        self.grace_periods.insert(id, grace_period);
+      }
+      None => self.default_grace_period = grace_period,
+    }
+  }
+
+  fn get_grace_period(&self, game_id: &str) -> Duration {
+    self
+      .grace_periods
+      .get(game_id)
+      .copied()
+      .unwrap_or(self.default_grace_period)
+  }
+
+  /// Replace the matching rules of all games
+  pub fn set_matching_rules(&mut self, rules: Vec<CompiledRule>) {
+    self.rules = rules;
+  }
+
+  /// Whether known game paths are also registered with their symlinks resolved, the keys of
+  /// current games are registered again if it changes
+  pub fn set_resolve_symlinks(&mut self, resolve_symlinks: bool) {
+    if self.resolve_symlinks == resolve_symlinks {
+      return;
+    }
+    self.resolve_symlinks = resolve_symlinks;
+    self.known_games = KnownGames::default();
+    let mut games: Vec<KnownGame> = self.known_game_defs.values().cloned().collect();
+    games.sort_by(|a, b| a.id.cmp(&b.id));
+    let mut libraries = None;
+    for game in games {
+      self.insert_known_game(game, &mut libraries);
+    }
+  }
+
+  fn get_matching_rule(&self, game_id: &str) -> Option<&CompiledRule> {
+    self.rules.iter().find(|rule| rule.game_id() == game_id)
+  }
+
   pub fn set_process_callback(
     &mut self,
     callback: Option<NapiWeakThreadsafeFunction<ProcessEvent, ()>>,
@@ -101,8 +532,195 @@ impl GameManager {
     self.process_callback = callback;
   }
 
-  pub fn add_known_game(&mut self, path: String, id: String) {
-    self.known_games.insert(normalize_os_path(path), id);
+  /// Track a process launched for a game, whatever its path is. The minimum lifetime of the game
+  /// does not apply, since the process is known to be the game.
+  ///
+  /// Returns `false` if the process is tracked already, e.g. its start has been reported first.
+  pub fn track_launched_process(&mut self, game_id: &str, msg: ProcessMessage) -> bool {
+    let identity = msg.identity();
+    self.remove_stale_processes(&identity);
+    if let Some(info) = self
+      .find_running(&identity)
+      .and_then(|key| self.running_process.get(&key))
+    {
+      if info.game_id != game_id {
+        log::info(
+          format!(
+            "launched process {} of game {} is tracked as game {}",
+            identity, game_id, info.game_id
+          )
+          .as_str(),
+        );
+      }
+      return false;
+    }
+    if let Some(pending) = self.pending_starts.remove(&msg.pid) {
+      pending.timer.abort();
+    }
+    let l_path = NormalizedPath::new(&msg.path);
+    self.on_game_process_started(msg, l_path, game_id.to_string());
+    if let Some(info) = self.running_process.get_mut(&identity) {
+      info.is_launched = true;
+    }
+    true
+  }
+
+  /// Track the descendants of a launched process as its game, e.g. before the launched process
+  /// exits and its children lose their parent. Processes excluded by the matching rule of the game
+  /// are skipped.
+  ///
+  /// Processes which started before the launched process are never its descendants, their parent
+  /// pid belongs to an earlier process. Returns the pids which are tracked now.
+  pub fn track_descendants(
+    &mut self,
+    game_id: &str,
+    root_pid: u32,
+    root_start_time: Option<i64>,
+    mut processes: Vec<ProcessEntry>,
+  ) -> Vec<u32> {
+    let root_start_time = root_start_time.unwrap_or_default();
+    processes.retain(|process| process.start_time >= root_start_time);
+    // parents start before their children
+    processes.sort_by_key(|process| (process.start_time, process.pid));
+    let mut tree = vec![root_pid];
+    let mut tracked = Vec::new();
+    for process in processes {
+      if process.pid == root_pid || !tree.contains(&process.ppid) {
+        continue;
+      }
+      tree.push(process.pid);
+      let msg = ProcessMessage::started(process.pid, process.ppid, process.path)
+        .with_start_time(process.start_time);
+      if self.track_game_member(game_id, msg) {
+        tracked.push(process.pid);
+      }
+    }
+    tracked
+  }
+
+  /// Track a process known to belong to a launched game, unless it is excluded by the matching rule
+  /// of the game. Returns `false` if it is not tracked now.
+  fn track_game_member(&mut self, game_id: &str, msg: ProcessMessage) -> bool {
+    let is_excluded = self
+      .get_matching_rule(game_id)
+      .is_some_and(|rule| rule.excludes(&NormalizedPath::new(&msg.path)));
+    !is_excluded && self.track_launched_process(game_id, msg)
+  }
+
+  /// Contain the processes of a launched game in a group, every process in the group belongs to the
+  /// game until it exits.
+  ///
+  /// Returns `true` if it is the first group of the game, which needs to be synced then, see
+  /// `sync_process_groups`.
+  pub fn add_process_group(&mut self, game_id: &str, group: Arc<dyn ProcessGroup>) -> bool {
+    let groups = self.process_groups.entry(game_id.to_string()).or_default();
+    groups.push(group);
+    groups.len() == 1
+  }
+
+  /// Pids of the running processes in the groups of a game
+  fn get_group_pids(&self, game_id: &str) -> Vec<u32> {
+    self
+      .process_groups
+      .get(game_id)
+      .into_iter()
+      .flatten()
+      .flat_map(|group| group.pids())
+      .collect()
+  }
+
+  /// Track the processes in the groups of a game which are not tracked yet, e.g. the detached
+  /// children of a double-forking launcher. Returns the pids which are tracked now.
+  fn adopt_group_processes(&mut self, game_id: &str) -> Vec<u32> {
+    let untracked: Vec<u32> = self
+      .get_group_pids(game_id)
+      .into_iter()
+      .filter(|pid| !self.running_process.values().any(|info| info.pid == *pid))
+      .collect();
+    if untracked.is_empty() {
+      return Vec::new();
+    }
+    let mut tracked = Vec::new();
+    for process in platform::get_all_process() {
+      if !untracked.contains(&process.pid) {
+        continue;
+      }
+      let msg = ProcessMessage::started(process.pid, process.ppid, process.path)
+        .with_start_time(process.start_time);
+      let identity = msg.identity();
+      if !self.track_game_member(game_id, msg) {
+        continue;
+      }
+      if let Some(info) = self.running_process.get_mut(&identity) {
+        info.is_contained = true;
+      }
+      tracked.push(process.pid);
+    }
+    tracked
+  }
+
+  /// Sync the tracked processes of a game with its process groups, for processes whose start or
+  /// exit is not reported by a monitor. Processes in a group are tracked, tracked members which
+  /// left their group have exited. Empty groups are dropped.
+  ///
+  /// The launched process itself is not a tracked member, its exit is reported by the launcher
+  /// with its exit code. Returns whether the game still has a group with processes in it.
+  pub fn sync_process_groups(&mut self, game_id: &str) -> bool {
+    if !self.process_groups.contains_key(game_id) {
+      return false;
+    }
+    self.adopt_group_processes(game_id);
+    // an exited process leaves its group even before it is reaped
+    let pids = self.get_group_pids(game_id);
+    let exited: Vec<ProcessIdentity> = self
+      .running_process
+      .iter()
+      .filter(|(_, info)| {
+        info.game_id == game_id
+          && info.is_contained
+          && info.status == ProcessStatus::Started
+          && !pids.contains(&info.pid)
+      })
+      .map(|(identity, _)| *identity)
+      .collect();
+    for identity in exited {
+      if let Some(info) = self.running_process.remove(&identity) {
+        self.on_game_process_terminated(info, None, None);
+      }
+    }
+    let Some(groups) = self.process_groups.get_mut(game_id) else {
+      return false;
+    };
+    groups.retain(|group| !group.pids().is_empty());
+    if groups.is_empty() {
+      self.process_groups.remove(game_id);
+      return false;
+    }
+    true
+  }
+
+  /// Pids of every running process of a game, both the tracked ones and the ones in its process
+  /// groups, e.g. a crash handler excluded by its matching rule
+  pub fn get_game_pids(&self, game_id: &str) -> Vec<u32> {
+    let mut pids = self.get_group_pids(game_id);
+    pids.extend(
+      self
+        .running_process
+        .values()
+        .filter(|info| info.game_id == game_id && info.status == ProcessStatus::Started)
+        .map(|info| info.pid),
+    );
+    pids.sort_unstable();
+    pids.dedup();
+    pids
+  }
+
+  /// Add a known game or replace the game with the same id, a disabled game is removed.
+  ///
+  /// Returns the error if the game is invalid, in which case nothing changes, or the conflicts of
+  /// the keys it is registered with.
+  pub fn add_known_game(&mut self, game: KnownGame) -> KnownGameUpdate {
+    self.apply_known_games(vec![game], Vec::new(), false)
   }
 
   pub fn remove_known_game_by_id(&mut self, game_id: &str, preserve_running_process: bool) {
@@ -110,53 +728,57 @@ impl GameManager {
       self
         .running_process
         .retain(|_, info| info.game_id != game_id);
+      if let Some(mut session) = self.sessions.remove(game_id) {
+        session.cancel_grace();
+      }
+      self.grace_periods.remove(game_id);
+      self.pending_starts.retain(|_, pending| {
+        let is_removed = pending.game_id == game_id;
+        if is_removed {
+          pending.timer.abort();
+        }
+        !is_removed
+      });
+      self.process_groups.remove(game_id);
     }
-    self.known_games.retain(|_, id| id != game_id);
+    self.known_games.remove_game(game_id);
+    self.known_game_defs.remove(game_id);
   }
 
-  pub fn get_known_game_id_exact(&self, l_path: &str) -> Option<&String> {
-    self.known_games.get(l_path)
+  /// Look up a path by the full path, then its nearest known ancestor folder,
+  /// then its executable name, see `KnownGames::lookup`
+  pub fn get_known_game_id(&self, l_path: &NormalizedPath) -> Option<KnownGameMatch> {
+    self.known_games.lookup(l_path)
   }
 
-  pub fn get_known_game_id(&self, l_path: &str) -> Option<&String> {
-    let (l_dir, l_exe) = match l_path.rsplit_once("\\") {
-      Some((l, r)) => (l, r),
-      None => return self.get_known_game_id_exact(l_path),
-    };
+  /// Get the game id of a running known game process, so that its children can be attributed to it
+  pub fn get_running_game_id(&self, pid: u32) -> Option<String> {
+    if pid == 0 {
+      return None;
+    }
     self
-      .get_known_game_id_exact(l_dir)
-      .or_else(|| self.get_known_game_id_exact(l_path))
-      .or_else(|| self.get_known_game_id_exact(l_exe))
+      .running_process
+      .values()
+      .find(|info| info.pid == pid)
+      .map(|info| info.game_id.clone())
+      .or_else(|| self.pending_starts.get(&pid).map(|p| p.game_id.clone()))
   }
 
+  /// Whether a process of the path is running, or any process below the path if it is a folder
   pub fn is_running(&self, path: String, is_folder: Option<bool>) -> bool {
-    let mut is_running = false;
-    let normalized_path = normalize_os_path(path);
-    let escaped_path = regex::escape(&normalized_path);
-
-    for (k, _) in &self.running_process {
-      let re = if is_folder.is_some_and(|x| x) {
-        Regex::new(format!(r"^{}[/\\][^/\\]+-\d+$", escaped_path).as_str())
+    let l_path = NormalizedPath::new(path);
+    let is_folder = is_folder.unwrap_or(false);
+    self.running_process.values().any(|info| {
+      if is_folder {
+        info.path != l_path && info.path.is_under(&l_path)
       } else {
-        Regex::new(format!(r"^{}-\d+$", escaped_path).as_str())
-      };
-      match re {
-        Ok(re) => {
-          if re.is_match(k) {
-            is_running = true;
-            break;
-          }
-        }
-        Err(_) => {
-          continue;
-        }
+        info.path == l_path
       }
-    }
-    is_running
+    })
   }
 
   pub fn is_magpie_pid(pid: u32) -> bool {
-    let mut full_path = win32::get_process_full_path_by_pid(pid);
+    let mut full_path = platform::get_process_full_path_by_pid(pid);
     full_path.make_ascii_lowercase();
     if full_path.ends_with("magpie.exe") {
       return true;
@@ -170,11 +792,13 @@ impl GameManager {
     if Self::is_magpie_pid(msg) {
       return;
     }
-    for (_, info) in &self.running_process {
+    for info in self.running_process.values() {
       // if the incoming foreground pid is not a running game's pid, proceeds to the next iteration
       if info.pid != msg {
         continue;
       }
+      let game_id = info.game_id.clone();
+      self.set_foreground_session(Some(&game_id));
       // if the incoming foreground pid equals the previous foreground pid, do nothing
       // (this happens if a game has multiple windows and the user is switching between those windows)
       if self.foreground_pid == msg {
@@ -182,7 +806,6 @@ impl GameManager {
       }
       // a game window comes into foreground, send the message to Node
       self.foreground_pid = msg;
-      let game_id = info.game_id.clone();
       let timeout = self.foreground_wait_time;
       if let Some(handle) = self.foreground_timeout_handle.take() {
         handle.abort();
@@ -198,6 +821,7 @@ impl GameManager {
       return;
     }
     // no running games pid matched, user switched foreground window to a non game window
+    self.set_foreground_session(None);
 
     // if the previous foreground window pid is already a non game window pid, do nothing
     if self.foreground_pid == 0 {
@@ -219,148 +843,634 @@ impl GameManager {
     }));
   }
 
-  #[allow(dead_code)]
-  pub fn handle_wmi_message(&mut self, msg: ProcessMessage) {
-    let l_path = msg.path.to_lowercase();
-    // check directory & fullpath & process name
-    let game_id = match self.get_known_game_id(&l_path) {
-      Some(id) => id.clone(),
-      None => return,
-    };
-    let pid = msg.pid;
-    let key = format!("{l_path}-{}", msg.pid);
-    match msg.status {
-      ProcessStatus::Started => {
-        // handle race condition
-        if let Some(prev) = self.running_process.get(&key) {
-          if prev.status == ProcessStatus::Terminated {
-            // process termination notification is received before creation, remove it and return
-            self.running_process.remove(&key);
-            return;
-          } else if prev.status == ProcessStatus::Started {
-            // a process which has the same full path and pid with current message is already traced,
-            // may be a duplication delivery, ignore it
-            return;
-          }
-        }
-        self.running_process.insert(
-          key,
-          KnownGameProcessInfo {
-            pid: msg.pid,
-            status: msg.status,
-            path: l_path.clone(),
-            game_id: game_id.clone(),
-          },
-        );
-        self.foreground_pid = msg.pid;
-        log::info(format!("game started: {}, pid: {}", l_path, pid).as_str());
-        if let Some(callback) = &self.process_callback {
-          callback.call(
-            Ok(ProcessEvent {
-              event_type: ProcessEventType::Creation,
-              full_path: l_path,
-              pid: pid,
-              id: game_id,
-            }),
-            ThreadsafeFunctionCallMode::Blocking,
-          );
+  /// Find the known game of a process by its path, then by its alternative paths.
+  ///
+  /// Returns the match and the normalized path that matched, so that a game added with a
+  /// Windows path keeps being reported with it when running in Wine.
+  /// Aliases which are not paths (e.g. Steam AppId keys) report the process path instead.
+  /// A unique match of any of them is preferred to an ambiguous one.
+  pub fn lookup_known_game(
+    &self,
+    path: String,
+    aliases: Vec<String>,
+  ) -> Option<(KnownGameMatch, NormalizedPath)> {
+    let l_path = NormalizedPath::new(path);
+    let mut ambiguous = None;
+    let l_keys = aliases.into_iter().map(NormalizedPath::new);
+    for l_key in std::iter::once(l_path.clone()).chain(l_keys) {
+      let Some(found) = self.get_known_game_id(&l_key) else {
+        continue;
+      };
+      let l_matched = if steam::parse_app_id_key(l_key.as_str()).is_some() {
+        l_path.clone()
+      } else {
+        l_key
+      };
+      match found {
+        KnownGameMatch::Unique(_) => return Some((found, l_matched)),
+        KnownGameMatch::Ambiguous(_) => {
+          ambiguous.get_or_insert((found, l_matched));
         }
       }
-      ProcessStatus::Terminated => {
-        if let Some(prev) = self.running_process.get(&key) {
-          if prev.status == ProcessStatus::Started {
-            self.running_process.remove(&key);
-            self.foreground_pid = 0;
-            log::info(format!("game stopped: {}, pid: {}", l_path, pid).as_str());
-            if let Some(callback) = &self.process_callback {
-              callback.call(
-                Ok(ProcessEvent {
-                  event_type: ProcessEventType::Termination,
-                  full_path: l_path,
-                  pid: pid,
-                  id: game_id,
-                }),
-                ThreadsafeFunctionCallMode::Blocking,
-              );
-            }
-            return;
-          } else if prev.status == ProcessStatus::Terminated {
-            // a process which has the same full path and pid with current message is already traced,
-            // may be a duplication delivery, ignore it
-            return;
-          }
+    }
+    ambiguous
+  }
+
+  /// Find the known game a started process belongs to, returns the game id and the matched path.
+  ///
+  /// The process is identified by the known game paths, then by the matching rules, then by whether
+  /// its parent is a running game, since launchers may spawn the actual game from elsewhere.
+  /// Processes identified by themselves must meet the requirements of the rule of their game,
+  /// while the exclusions of the rule apply to all processes.
+  ///
+  /// A process matching a key shared by several games is attributed by its parent if the parent is
+  /// running one of those games, then to the `preferred` game it is already tracked with,
+  /// otherwise a warning event is emitted instead of guessing.
+  fn attribute_process(
+    &self,
+    msg: &ProcessMessage,
+    preferred: Option<&str>,
+  ) -> Option<(String, NormalizedPath)> {
+    let mut ambiguous = None;
+    let identified = match self.lookup_known_game(msg.path.clone(), msg.aliases.clone()) {
+      Some((KnownGameMatch::Unique(id), l_path)) => Some((id, l_path)),
+      Some((KnownGameMatch::Ambiguous(ids), l_path)) => {
+        ambiguous = Some((ids, l_path));
+        None
+      }
+      None => None,
+    }
+    .or_else(|| self.find_rule_game(&msg.path, &msg.aliases));
+    if let (None, Some((ids, l_path))) = (&identified, ambiguous) {
+      let parent_id = self.get_running_game_id(msg.ppid);
+      return match parent_id.or_else(|| preferred.map(str::to_string)) {
+        Some(id) if ids.contains(&id) => Some((id, l_path)),
+        _ => {
+          self.emit_ambiguous_match(msg, l_path, ids);
+          None
         }
-        // unsual case, maybe a race condition, insert the message
-        self.running_process.insert(
-          key,
-          KnownGameProcessInfo {
-            pid: msg.pid,
-            status: msg.status,
-            path: l_path,
-            game_id: game_id,
-          },
-        );
+      };
+    }
+    let (game_id, l_path) = match identified {
+      Some((game_id, l_path)) => {
+        let is_accepted = self.get_matching_rule(&game_id).is_none_or(|rule| {
+          // the command line is only queried once the process is identified as a game
+          let cmdline = || {
+            msg
+              .details
+              .cmdline
+              .clone()
+              .or_else(|| platform::get_process_details(msg.pid).cmdline)
+          };
+          rule.accepts(cmdline, || {
+            let image = platform::resolve_process_image(
+              msg.ppid,
+              platform::get_process_full_path_by_pid(msg.ppid),
+            );
+            std::iter::once(image.path)
+              .chain(image.aliases)
+              .map(NormalizedPath::new)
+              .collect()
+          })
+        });
+        if !is_accepted {
+          return None;
+        }
+        (game_id, l_path)
       }
+      None => (
+        self.get_running_game_id(msg.ppid)?,
+        NormalizedPath::new(&msg.path),
+      ),
+    };
+    if self
+      .get_matching_rule(&game_id)
+      .is_some_and(|rule| rule.excludes(&l_path))
+    {
+      return None;
     }
+    Some((game_id, l_path))
+  }
+
+  /// Find the game whose matching rule identifies a process by its path or alternative paths
+  fn find_rule_game(&self, path: &str, aliases: &[String]) -> Option<(String, NormalizedPath)> {
+    std::iter::once(path)
+      .chain(aliases.iter().map(String::as_str))
+      .filter(|path| steam::parse_app_id_key(path).is_none())
+      .map(NormalizedPath::new)
+      .find_map(|l_path| {
+        let rule = self.rules.iter().find(|rule| rule.identifies(&l_path))?;
+        Some((rule.game_id().to_string(), l_path))
+      })
   }
 
   pub fn handle_process_message(&mut self, msg: ProcessMessage) {
-    let l_path = msg.path.to_lowercase();
     match msg.status {
       ProcessStatus::Started => {
-        // check directory & fullpath & process name
-        let game_id = match self.get_known_game_id(&l_path) {
-          Some(id) => id.clone(),
-          None => return,
+        let identity = msg.identity();
+        self.remove_stale_processes(&identity);
+        let Some((game_id, l_path)) = self.attribute_process(&msg, None) else {
+          return;
         };
-        // create a unique key using path and pid combination, to handle multiple instances case
-        let key = format!("{l_path}-{}", msg.pid);
         // if already have it, may be a dulplication event
-        if self.running_process.get(&key).is_some() {
+        if self.find_running(&identity).is_some() || self.pending_starts.contains_key(&msg.pid) {
           return;
         }
-
-        self.running_process.insert(
-          key,
-          KnownGameProcessInfo {
-            pid: msg.pid,
-            status: msg.status,
-            path: l_path.clone(),
-            game_id: game_id.clone(),
-          },
-        );
-        self.foreground_pid = msg.pid;
-        log::info(format!("game started: {}, pid: {}", l_path, msg.pid).as_str());
-        if let Some(callback) = &self.process_callback {
-          callback.call(
-            Ok(ProcessEvent {
-              event_type: ProcessEventType::Creation,
-              full_path: l_path,
-              pid: msg.pid,
-              id: game_id,
-            }),
-            ThreadsafeFunctionCallMode::Blocking,
-          );
-        }
+        self.start_game_process(msg, l_path, game_id);
       }
       ProcessStatus::Terminated => {
-        for (_, game_info) in self.running_process.extract_if(|_, v| v.pid == msg.pid) {
-          log::info(format!("game stopped: {}, pid: {}", game_info.path, game_info.pid).as_str());
-          if let Some(callback) = &self.process_callback {
-            callback.call(
-              Ok(ProcessEvent {
-                event_type: ProcessEventType::Termination,
-                full_path: game_info.path,
-                pid: game_info.pid,
-                id: game_info.game_id,
-              }),
-              ThreadsafeFunctionCallMode::Blocking,
-            );
-          }
+        let identity = msg.identity();
+        self.cancel_pending_start(&identity);
+        let exited: Vec<_> = self
+          .running_process
+          .extract_if(|key, _| key.matches(&identity))
+          .map(|(_, info)| info)
+          .collect();
+        for info in exited {
+          self.on_game_process_terminated(info, msg.exit_code, msg.resources);
         }
       }
     }
   }
+
+  /// Find the key of a tracked process which may be the given one
+  fn find_running(&self, identity: &ProcessIdentity) -> Option<ProcessIdentity> {
+    self
+      .running_process
+      .keys()
+      .find(|key| key.matches(identity))
+      .copied()
+  }
+
+  /// Forget tracked processes whose pid is reused by a newly started process.
+  ///
+  /// Their exit was missed, e.g. the polling monitor did not poll in between or the exit event was
+  /// lost, so they are terminated now instead of keeping their sessions alive forever.
+  fn remove_stale_processes(&mut self, identity: &ProcessIdentity) {
+    let is_stale = |key: &ProcessIdentity| key.pid == identity.pid && !key.matches(identity);
+    let stale_pending = self
+      .pending_starts
+      .get(&identity.pid)
+      .map(|pending| pending.identity(identity.pid))
+      .filter(|key| is_stale(key));
+    if let Some(key) = stale_pending {
+      self.cancel_pending_start(&key);
+    }
+    let stale: Vec<_> = self
+      .running_process
+      .extract_if(|key, _| is_stale(key))
+      .map(|(_, info)| info)
+      .collect();
+    for info in stale {
+      log::info(
+        format!(
+          "process {} of game {} is replaced by process {}, its exit was missed",
+          info.identity(),
+          info.game_id,
+          identity
+        )
+        .as_str(),
+      );
+      self.on_game_process_terminated(info, None, None);
+    }
+  }
+
+  /// Sample the resources of all running known game processes, see `KnownGameProcessInfo::resources`
+  pub fn sample_resources(&mut self) {
+    self
+      .running_process
+      .values_mut()
+      .filter(|info| info.status == ProcessStatus::Started)
+      .for_each(KnownGameProcessInfo::sample_resources);
+  }
+
+  /// Get the summary of the running session of a game
+  pub fn get_session_summary(&self, game_id: &str) -> Option<SessionSummary> {
+    self
+      .sessions
+      .get(game_id)
+      .map(|session| session.summary(None))
+  }
+
+  /// Start tracking a known game process once it has lived for the minimum lifetime of its game
+  fn start_game_process(&mut self, msg: ProcessMessage, l_path: NormalizedPath, game_id: String) {
+    let pid = msg.pid;
+    let min_lifetime = self
+      .get_matching_rule(&game_id)
+      .map(|rule| rule.min_lifetime())
+      .unwrap_or_default();
+    // processes found by the startup scan may have been running for a while
+    let lifetime = msg
+      .details
+      .start_time
+      .map(|start_time| (chrono::Utc::now().timestamp_millis() - start_time).max(0) as u64)
+      .unwrap_or(0);
+    let remaining = min_lifetime.saturating_sub(Duration::from_millis(lifetime));
+    if remaining.is_zero() {
+      self.on_game_process_started(msg, l_path, game_id);
+      return;
+    }
+    let timer = tokio::spawn(async move {
+      tokio::time::sleep(remaining).await;
+      get().lock().await.confirm_pending_start(pid);
+    });
+    self.pending_starts.insert(
+      pid,
+      PendingStart {
+        l_path,
+        game_id,
+        msg,
+        timer,
+      },
+    );
+  }
+
+  /// Start tracking a process which has lived for the minimum lifetime of its game
+  pub fn confirm_pending_start(&mut self, pid: u32) {
+    if let Some(pending) = self.pending_starts.remove(&pid) {
+      self.on_game_process_started(pending.msg, pending.l_path, pending.game_id);
+    }
+  }
+
+  /// Forget a process which exited before its minimum lifetime, returns `false` if it is not pending
+  fn cancel_pending_start(&mut self, identity: &ProcessIdentity) -> bool {
+    let pid = identity.pid;
+    let is_pending = self
+      .pending_starts
+      .get(&pid)
+      .is_some_and(|pending| pending.identity(pid).matches(identity));
+    if !is_pending {
+      return false;
+    }
+    let Some(pending) = self.pending_starts.remove(&pid) else {
+      return false;
+    };
+    pending.timer.abort();
+    log::info(
+      format!(
+        "ignored short-lived process of game {}: {}, pid: {}",
+        pending.game_id, pending.l_path, pid
+      )
+      .as_str(),
+    );
+    true
+  }
+
+  /// Track a started known game process, a new session is started if it is the first process of the game
+  fn on_game_process_started(
+    &mut self,
+    mut msg: ProcessMessage,
+    l_path: NormalizedPath,
+    game_id: String,
+  ) {
+    msg.load_details();
+    let pid = msg.pid;
+    let info = KnownGameProcessInfo {
+      pid,
+      ppid: msg.ppid,
+      status: ProcessStatus::Started,
+      path: l_path.clone(),
+      image: ProcessImage {
+        path: msg.path,
+        aliases: msg.aliases,
+      },
+      game_id: game_id.clone(),
+      details: msg.details,
+      exit_code: None,
+      exit_handle: platform::open_process_exit_handle(pid).map(Arc::from),
+      resources: None,
+      is_launched: false,
+      is_contained: false,
+    };
+    // the pid alone is not unique over time, see `ProcessIdentity`
+    self.running_process.insert(info.identity(), info.clone());
+    self.foreground_pid = pid;
+    log::info(format!("game started: {}, pid: {}", l_path, pid).as_str());
+
+    let session = self.sessions.entry(game_id.clone()).or_insert_with(|| {
+      let session = GameSession::new(game_id.clone());
+      log::info(
+        format!(
+          "game session started: {}, id: {}",
+          game_id,
+          session.session_id()
+        )
+        .as_str(),
+      );
+      session
+    });
+    // a game restarted within the grace period continues its previous session
+    let is_resumed = session.cancel_grace();
+    if is_resumed {
+      log::info(
+        format!(
+          "game session resumed: {}, id: {}",
+          game_id,
+          session.session_id()
+        )
+        .as_str(),
+      );
+    }
+    let is_first = session.is_empty() && !is_resumed;
+    session.add_process(pid);
+    let summary = session.summary(None);
+    if is_first {
+      self.emit_process_event(ProcessEventType::SessionStarted, &info, summary.clone());
+    }
+    self.emit_process_event(ProcessEventType::Creation, &info, summary);
+  }
+
+  /// Track an exited known game process, the session is ended if it is the last process of the game.
+  ///
+  /// The exit code and resources are those reported by the monitor, if any.
+  fn on_game_process_terminated(
+    &mut self,
+    mut info: KnownGameProcessInfo,
+    exit_code: Option<i64>,
+    resources: Option<ProcessResources>,
+  ) {
+    info.record_exit(exit_code, resources);
+    log::info(
+      format!(
+        "game stopped: {}, pid: {}, exit code: {}",
+        info.path,
+        info.pid,
+        info
+          .exit_code
+          .map_or_else(|| "unknown".to_string(), |code| code.to_string())
+      )
+      .as_str(),
+    );
+    // a contained game runs as long as its groups have processes, it is not restarting once they
+    // are empty
+    let is_contained = self.process_groups.contains_key(&info.game_id);
+    if is_contained {
+      self.adopt_group_processes(&info.game_id);
+    }
+    let grace_period = if is_contained {
+      Duration::ZERO
+    } else {
+      self.get_grace_period(&info.game_id)
+    };
+    let Some(session) = self.sessions.get_mut(&info.game_id) else {
+      return;
+    };
+    session.remove_process(info.pid);
+    if let Some(resources) = &info.resources {
+      session.add_resources(resources);
+    }
+    if !session.is_empty() {
+      let summary = session.summary(None);
+      self.emit_process_event(ProcessEventType::Termination, &info, summary);
+      return;
+    }
+    session.set_foreground(false);
+    let end_time = chrono::Utc::now().timestamp_millis();
+    if grace_period.is_zero() {
+      // the groups of a contained game only have excluded processes left
+      self.process_groups.remove(&info.game_id);
+      if let Some(session) = self.sessions.remove(&info.game_id) {
+        self.end_session(session, &info, end_time);
+      }
+      return;
+    }
+    // defer the termination until the grace period expires, in case the game is restarting
+    let game_id = info.game_id.clone();
+    let session_id = session.session_id().to_string();
+    let timer = tokio::spawn(async move {
+      tokio::time::sleep(grace_period).await;
+      get().lock().await.expire_session(&game_id, &session_id);
+    });
+    log::info(
+      format!(
+        "game session is ending: {}, id: {}, grace period: {}s",
+        info.game_id,
+        session.session_id(),
+        grace_period.as_secs()
+      )
+      .as_str(),
+    );
+    session.begin_grace(PendingEnd::new(info, end_time, timer));
+  }
+
+  /// Stop attributing a running process to its game, e.g. because the game has been removed.
+  ///
+  /// The session ends right away if this was its last process, since the game is not restarting.
+  fn detach_game_process(&mut self, mut info: KnownGameProcessInfo) {
+    info.sample_resources();
+    log::info(
+      format!(
+        "process {} no longer belongs to game {}: {}",
+        info.identity(),
+        info.game_id,
+        info.path
+      )
+      .as_str(),
+    );
+    if self.foreground_pid == info.pid {
+      self.foreground_pid = 0;
+    }
+    let Some(session) = self.sessions.get_mut(&info.game_id) else {
+      return;
+    };
+    session.remove_process(info.pid);
+    if let Some(resources) = &info.resources {
+      session.add_resources(resources);
+    }
+    if !session.is_empty() {
+      let summary = session.summary(None);
+      self.emit_process_event(ProcessEventType::Termination, &info, summary);
+      return;
+    }
+    session.set_foreground(false);
+    if let Some(session) = self.sessions.remove(&info.game_id) {
+      self.end_session(session, &info, chrono::Utc::now().timestamp_millis());
+    }
+  }
+
+  /// End the session of a removed game right away if it is waiting for the game to restart
+  fn end_ending_session(&mut self, game_id: &str) {
+    let is_ending = self
+      .sessions
+      .get(game_id)
+      .is_some_and(GameSession::is_ending);
+    if !is_ending {
+      return;
+    }
+    let Some(mut session) = self.sessions.remove(game_id) else {
+      return;
+    };
+    // the grace timer finds no session to expire then
+    if let Some(pending_end) = session.take_pending_end() {
+      self.end_session(session, &pending_end.process, pending_end.end_time);
+    }
+  }
+
+  /// End a session whose grace period expired without the game restarting
+  pub fn expire_session(&mut self, game_id: &str, session_id: &str) {
+    let is_expired = self
+      .sessions
+      .get(game_id)
+      .is_some_and(|session| session.session_id() == session_id && session.is_ending());
+    if !is_expired {
+      return;
+    }
+    let Some(mut session) = self.sessions.remove(game_id) else {
+      return;
+    };
+    if let Some(pending_end) = session.take_pending_end() {
+      self.end_session(session, &pending_end.process, pending_end.end_time);
+    }
+  }
+
+  /// Deliver the termination of the last process and the end of its session
+  fn end_session(&self, session: GameSession, process: &KnownGameProcessInfo, end_time: i64) {
+    let summary = session.summary(Some(end_time));
+    log::info(
+      format!(
+        "game session ended: {}, id: {}, foreground time: {}ms",
+        summary.game_id, summary.session_id, summary.foreground_time
+      )
+      .as_str(),
+    );
+    self.emit_process_event(ProcessEventType::Termination, process, summary.clone());
+    self.emit_process_event(ProcessEventType::SessionEnded, process, summary);
+  }
+
+  fn emit_process_event(
+    &self,
+    event_type: ProcessEventType,
+    process: &KnownGameProcessInfo,
+    session: SessionSummary,
+  ) {
+    let crashed = process
+      .exit_code
+      .map(|exit_code| is_crash(exit_code, &session));
+    if crashed == Some(true) {
+      log::info(
+        format!(
+          "game crashed: {}, pid: {}, exit code: {}",
+          process.game_id,
+          process.pid,
+          process.exit_code.unwrap_or_default()
+        )
+        .as_str(),
+      );
+    }
+    if let Some(callback) = &self.process_callback {
+      callback.call(
+        Ok(ProcessEvent {
+          event_type,
+          full_path: process.path.to_string(),
+          pid: process.pid,
+          id: process.game_id.clone(),
+          cmdline: process.details.cmdline.clone(),
+          cwd: process.details.cwd.clone(),
+          start_time: process.details.start_time,
+          exit_code: process.exit_code,
+          crashed,
+          resources: process.resources.as_ref().map(Into::into),
+          session: Some(session),
+          candidates: None,
+        }),
+        ThreadsafeFunctionCallMode::Blocking,
+      );
+    }
+  }
+
+  /// Warn that a process matches several games and is not attributed to any of them
+  fn emit_ambiguous_match(
+    &self,
+    msg: &ProcessMessage,
+    l_path: NormalizedPath,
+    game_ids: Vec<String>,
+  ) {
+    log::info(
+      format!(
+        "process {} (pid: {}) matches multiple games: {}",
+        l_path,
+        msg.pid,
+        game_ids.join(", ")
+      )
+      .as_str(),
+    );
+    if let Some(callback) = &self.process_callback {
+      // details are only loaded for game processes, see `ProcessMessage::load_details`
+      let details = platform::get_process_details(msg.pid);
+      callback.call(
+        Ok(ProcessEvent {
+          event_type: ProcessEventType::AmbiguousMatch,
+          full_path: l_path.into_string(),
+          pid: msg.pid,
+          id: String::new(),
+          cmdline: details.cmdline,
+          cwd: details.cwd,
+          start_time: msg.details.start_time.or(details.start_time),
+          exit_code: None,
+          crashed: None,
+          resources: None,
+          session: None,
+          candidates: Some(game_ids),
+        }),
+        ThreadsafeFunctionCallMode::Blocking,
+      );
+    }
+  }
+
+  /// Accumulate foreground time to the session of the game in foreground, `None` if no game is
+  fn set_foreground_session(&mut self, game_id: Option<&str>) {
+    for (id, session) in self.sessions.iter_mut() {
+      session.set_foreground(game_id == Some(id.as_str()));
+    }
+  }
+}
+
+/// Check that a known game can be registered, i.e. it has an id and its paths suit its match mode
+pub fn validate_known_game(game: &KnownGame) -> Result<(), String> {
+  if game.id.trim().is_empty() {
+    return Err("id is empty".to_string());
+  }
+  if game.paths.is_empty() {
+    return Err("no paths are given".to_string());
+  }
+  let needs_path = matches!(
+    game.match_mode,
+    Some(
+      KnownGameMatchMode::File | KnownGameMatchMode::Folder | KnownGameMatchMode::FolderRecursive
+    )
+  );
+  for path in game.paths.iter() {
+    if path.trim().is_empty() {
+      return Err("a path is empty".to_string());
+    }
+    if needs_path && !NormalizedPath::new(path).is_path() && steam::parse_app_id_key(path).is_none()
+    {
+      return Err(format!(
+        "{} is not a path, which match mode {:?} requires",
+        path,
+        game.match_mode.unwrap_or(KnownGameMatchMode::ExeName)
+      ));
+    }
+  }
+  Ok(())
+}
+
+fn known_game_error(game: &KnownGame, index: usize, reason: String) -> KnownGameError {
+  log::info(format!("known game {} is rejected: {}", game.id, reason).as_str());
+  KnownGameError {
+    id: game.id.clone(),
+    index: index as u32,
+    reason,
+  }
+}
+
+/// Whether a process exit means the game crashed, i.e. it failed within `CRASH_WINDOW` of the
+/// start of its session
+pub fn is_crash(exit_code: i64, session: &SessionSummary) -> bool {
+  let end_time = session
+    .end_time
+    .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+  exit_code != 0 && end_time - session.start_time < CRASH_WINDOW.as_millis() as i64
 }
 
 static GAME_MANAGER: LazyLock<Mutex<GameManager>> =
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use crate::{napi_monitor::KnownGameMatchMode, utils::path::NormalizedPath};
+
+/// Result of looking up a process path in known games
+#[derive(Debug, PartialEq)]
+pub enum KnownGameMatch {
+  /// The most specific matching key belongs to a single game
+  Unique(String),
+  /// The most specific matching key is shared by several games, in the order they were added
+  Ambiguous(Vec<String>),
+}
+
+impl KnownGameMatch {
+  fn from_ids(ids: &[String]) -> Option<Self> {
+    match ids {
+      [] => None,
+      [id] => Some(Self::Unique(id.clone())),
+      ids => Some(Self::Ambiguous(ids.to_vec())),
+    }
+  }
+}
+
+/// A node of the path component trie, one level per component
+#[derive(Default)]
+struct PathNode {
+  children: HashMap<String, PathNode>,
+  /// The normalized key and its games with their match modes, if known games are registered with
+  /// this exact path
+  entry: Option<(NormalizedPath, Vec<(String, KnownGameMatchMode)>)>,
+}
+
+impl PathNode {
+  /// Remove a game from this subtree, returns whether the node became useless
+  fn remove_game(&mut self, game_id: &str) -> bool {
+    if let Some((_, games)) = &mut self.entry {
+      games.retain(|(id, _)| id != game_id);
+      if games.is_empty() {
+        self.entry = None;
+      }
+    }
+    self.children.retain(|_, child| !child.remove_game(game_id));
+    self.entry.is_none() && self.children.is_empty()
+  }
+
+  /// Games of this node whose match mode applies to a path with `remaining` more components
+  fn matching_ids(&self, remaining: usize) -> Vec<String> {
+    let Some((_, games)) = &self.entry else {
+      return Vec::new();
+    };
+    games
+      .iter()
+      .filter(|(_, mode)| match remaining {
+        0 => matches!(
+          mode,
+          KnownGameMatchMode::File | KnownGameMatchMode::FolderRecursive
+        ),
+        1 => matches!(
+          mode,
+          KnownGameMatchMode::Folder | KnownGameMatchMode::FolderRecursive
+        ),
+        _ => *mode == KnownGameMatchMode::FolderRecursive,
+      })
+      .map(|(id, _)| id.clone())
+      .collect()
+  }
+
+  fn collect_entries<'a>(&'a self, entries: &mut Vec<(&'a str, Vec<&'a String>)>) {
+    if let Some((key, games)) = &self.entry {
+      let mut ids: Vec<&String> = Vec::with_capacity(games.len());
+      for (id, _) in games {
+        if !ids.contains(&id) {
+          ids.push(id);
+        }
+      }
+      entries.push((key.as_str(), ids));
+    }
+    for child in self.children.values() {
+      child.collect_entries(entries);
+    }
+  }
+}
+
+/// Known game keys (normalized path - game ids).
+///
+/// Keys are either paths (file or folder) or bare names (executable name, Steam AppId key).
+/// Paths are indexed by their components, so that a folder matches every process below it with a
+/// single walk from the root, no matter how many games are known. How a path key matches is
+/// decided by the match mode of each game registered with it.
+#[derive(Default)]
+pub struct KnownGames {
+  root: PathNode,
+  /// Keys without path separators, and file names of keys in `exe-name` mode
+  names: HashMap<String, Vec<String>>,
+}
+
+impl KnownGames {
+  /// Register a game with a key, a key may be shared by several games.
+  ///
+  /// Without a match mode, a path also matches the processes below it and a bare name matches
+  /// executables of that name. A key in `exe-name` mode is registered with its file name, bare
+  /// names are always matched by name.
+  pub fn insert(
+    &mut self,
+    l_key: NormalizedPath,
+    game_id: String,
+    mode: Option<KnownGameMatchMode>,
+  ) {
+    let mode = mode.unwrap_or(if l_key.is_path() {
+      KnownGameMatchMode::FolderRecursive
+    } else {
+      KnownGameMatchMode::ExeName
+    });
+    if mode == KnownGameMatchMode::ExeName || !l_key.is_path() {
+      let ids = self.names.entry(l_key.file_name().to_string()).or_default();
+      if !ids.contains(&game_id) {
+        ids.push(game_id);
+      }
+      return;
+    }
+    let mut node = &mut self.root;
+    for component in l_key.components() {
+      node = node.children.entry(component.to_string()).or_default();
+    }
+    let games = &mut node.entry.get_or_insert_with(|| (l_key, Vec::new())).1;
+    if !games.iter().any(|(id, m)| *id == game_id && *m == mode) {
+      games.push((game_id, mode));
+    }
+  }
+
+  pub fn remove_game(&mut self, game_id: &str) {
+    self.root.remove_game(game_id);
+    self.names.retain(|_, ids| {
+      ids.retain(|id| id != game_id);
+      !ids.is_empty()
+    });
+  }
+
+  /// Look up a path by the path itself or its nearest registered ancestor folder,
+  /// then by its executable name.
+  ///
+  /// Only games whose match mode applies at the depth of the path count, e.g. a `folder` key
+  /// matches its direct children only. The most specific key found decides the match, a less
+  /// specific key never overrides it.
+  pub fn lookup(&self, l_path: &NormalizedPath) -> Option<KnownGameMatch> {
+    let depth = l_path.components().count();
+    let mut node = &self.root;
+    let mut deepest = Vec::new();
+    for (i, component) in l_path.components().enumerate() {
+      let Some(child) = node.children.get(component) else {
+        break;
+      };
+      node = child;
+      let ids = node.matching_ids(depth - i - 1);
+      if !ids.is_empty() {
+        deepest = ids;
+      }
+    }
+    if let Some(found) = KnownGameMatch::from_ids(&deepest) {
+      return Some(found);
+    }
+    KnownGameMatch::from_ids(self.names.get(l_path.file_name())?)
+  }
+
+  /// Get all keys shared by several games, sorted by key
+  pub fn get_shared_keys(&self) -> Vec<(String, Vec<String>)> {
+    let mut entries = Vec::new();
+    self.root.collect_entries(&mut entries);
+    entries.extend(
+      self
+        .names
+        .iter()
+        .map(|(key, ids)| (key.as_str(), ids.iter().collect())),
+    );
+    let mut shared: Vec<(String, Vec<String>)> = entries
+      .into_iter()
+      .filter(|(_, ids)| ids.len() > 1)
+      .map(|(key, ids)| (key.to_string(), ids.into_iter().cloned().collect()))
+      .collect();
+    shared.sort();
+    shared
+  }
+}
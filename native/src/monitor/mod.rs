@@ -1,89 +1,365 @@
-use std::sync::LazyLock;
+use std::{sync::LazyLock, time::Duration};
 use tokio::sync::Mutex;
 
+#[cfg(target_os = "linux")]
+use crate::monitor::netlink_monitor::NetlinkMonitor;
+#[cfg(windows)]
+use crate::monitor::{etw_monitor::EtwMonitor, wmi_monitor::WmiMonitor};
 use crate::{
   log,
-  monitor::{etw_monitor::EtwMonitor, polling_monitor::PollingMonitor},
-  napi_monitor::ProcessEvent,
+  monitor::polling_monitor::PollingMonitor,
+  monitor::rules::{CompiledRule, RuleError},
+  napi_monitor::{
+    KnownGame, KnownGamePatch, KnownGameUpdate, MatchingRule, MonitorBackend, MonitorFailure,
+    MonitorStatus, ProcessEvent, SessionSummary,
+  },
+  platform,
   utils::types::NapiWeakThreadsafeFunction,
-  win32,
 };
 
 pub mod gm;
+pub mod known_games;
+pub mod rules;
+mod session;
 
+#[cfg(windows)]
 mod etw_monitor;
-mod polling_monitor;
+#[cfg(target_os = "linux")]
+mod netlink_monitor;
+pub mod polling_monitor;
+#[cfg(windows)]
+mod wmi_monitor;
 
 trait WinProcessMonitor: Send {
-  fn start_monitoring(&mut self) -> windows_core::Result<()>;
+  fn start_monitoring(&mut self) -> std::io::Result<()>;
   fn stop_monitoring(&mut self);
   fn manual_update_process_status(&mut self);
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 enum ProcessStatus {
   Started,
   Terminated,
 }
 
+/// Identity of a process which survives pid reuse.
+///
+/// The OS reuses the pid of an exited process, so a pid is paired with the start time of the
+/// process. Every backend reports the start time in milliseconds since UNIX epoch, so that a
+/// process found by one source (e.g. the startup scan) is recognized when another reports its exit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct ProcessIdentity {
+  pub pid: u32,
+  /// `None` if the start time could not be retrieved, e.g. the process exited too early
+  pub start_time: Option<i64>,
+}
+
+impl ProcessIdentity {
+  pub fn new(pid: u32, start_time: Option<i64>) -> Self {
+    Self { pid, start_time }
+  }
+
+  /// Whether both identities may refer to the same process, an unknown start time matches any
+  pub fn matches(&self, other: &ProcessIdentity) -> bool {
+    self.pid == other.pid
+      && match (self.start_time, other.start_time) {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+      }
+  }
+}
+
+impl std::fmt::Display for ProcessIdentity {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self.start_time {
+      Some(start_time) => write!(f, "{}@{}", self.pid, start_time),
+      None => write!(f, "{}", self.pid),
+    }
+  }
+}
+
 pub struct ProcessMessage {
   pid: u32,
+  /// Parent process PID, 0 if it is unknown
+  ppid: u32,
   status: ProcessStatus,
   path: String,
+  /// Alternative paths the process can be matched with, see `platform::ProcessImage`
+  aliases: Vec<String>,
+  /// What the process was launched with. Only the start time is set until the process is known to
+  /// be a game process, see `load_details`. Empty for terminated processes.
+  details: platform::ProcessDetails,
+  /// Exit code of a terminated process, `None` if the backend does not report it
+  exit_code: Option<i64>,
+  /// Resources a terminated process consumed, `None` if the backend does not report them
+  resources: Option<platform::ProcessResources>,
+}
+
+impl ProcessMessage {
+  /// Create a process start message, resolving the program the process is actually running.
+  ///
+  /// The start time should be set with `with_start_time` or `query_start_time`.
+  pub(crate) fn started(pid: u32, ppid: u32, path: String) -> Self {
+    let image = platform::resolve_process_image(pid, path);
+    Self {
+      pid,
+      ppid,
+      status: ProcessStatus::Started,
+      path: image.path,
+      aliases: image.aliases,
+      details: platform::ProcessDetails::default(),
+      exit_code: None,
+      resources: None,
+    }
+  }
+
+  /// Query the start time of the process, for backends which do not report it
+  pub(crate) fn query_start_time(mut self) -> Self {
+    self.details.start_time = platform::get_process_start_time(self.pid);
+    self
+  }
+
+  /// Query the command line and working directory of the process. It is only done for game
+  /// processes, since it is too expensive for every process started on the system.
+  pub(crate) fn load_details(&mut self) {
+    if self.details.cmdline.is_some() {
+      return;
+    }
+    let details = platform::get_process_details(self.pid);
+    self.details.cmdline = details.cmdline;
+    self.details.cwd = details.cwd;
+    // the start time reported by the backend is available even if the process has exited
+    self.details.start_time = self.details.start_time.or(details.start_time);
+  }
+
+  /// Use the start time reported by the backend, which is available even if the process has exited
+  pub(crate) fn with_start_time(mut self, start_time: i64) -> Self {
+    if start_time > 0 {
+      self.details.start_time = Some(start_time);
+    }
+    self
+  }
+
+  /// Create a process exit message, the start time should be set with `with_start_time` if known
+  pub(crate) fn terminated(pid: u32, path: String) -> Self {
+    Self {
+      pid,
+      ppid: 0,
+      status: ProcessStatus::Terminated,
+      path,
+      aliases: Vec::new(),
+      details: platform::ProcessDetails::default(),
+      exit_code: None,
+      resources: None,
+    }
+  }
+
+  /// Use the exit code reported by the backend
+  pub(crate) fn with_exit_code(mut self, exit_code: i64) -> Self {
+    self.exit_code = Some(exit_code);
+    self
+  }
+
+  /// Use the resources reported by the backend
+  #[cfg_attr(not(windows), allow(dead_code))]
+  pub(crate) fn with_resources(mut self, resources: platform::ProcessResources) -> Self {
+    self.resources = Some(resources);
+    self
+  }
+
+  pub(crate) fn identity(&self) -> ProcessIdentity {
+    ProcessIdentity::new(self.pid, self.details.start_time)
+  }
+}
+
+/// Default interval of the polling monitor
+const DEFAULT_POLLING_INTERVAL: Duration = Duration::from_secs(5);
+/// How often the resources of running game processes are sampled, for backends which do not report
+/// them on exit. Linux releases the memory of a process before its exit is reported, so its peak
+/// can only be sampled while it is running.
+const RESOURCE_SAMPLING_INTERVAL: Duration = Duration::from_secs(10);
+/// Default delay of the startup process check, giving the application some time to settle down
+const DEFAULT_STARTUP_SCAN_DELAY: Duration = Duration::from_secs(10);
+
+/// How the native monitor should be started
+#[derive(Debug, Clone)]
+pub struct MonitorConfig {
+  /// The preferred backend, `None` to walk through the fallback chain of current platform
+  pub backend: Option<MonitorBackend>,
+  pub polling_interval: Duration,
+  /// Whether to check already running processes once the monitor is started
+  pub startup_scan: bool,
+  pub startup_scan_delay: Duration,
+  /// Whether known game paths are also registered with symlinks and junctions resolved
+  pub resolve_symlinks: bool,
+}
+
+impl Default for MonitorConfig {
+  fn default() -> Self {
+    Self {
+      backend: None,
+      polling_interval: DEFAULT_POLLING_INTERVAL,
+      startup_scan: true,
+      startup_scan_delay: DEFAULT_STARTUP_SCAN_DELAY,
+      resolve_symlinks: true,
+    }
+  }
+}
+
+impl MonitorConfig {
+  /// Monitor backends to try, in the order of preference.
+  ///
+  /// A preferred backend falls back to polling only, which works everywhere.
+  pub fn fallback_chain(&self) -> Vec<MonitorBackend> {
+    match self.backend {
+      Some(MonitorBackend::Polling) => vec![MonitorBackend::Polling],
+      Some(backend) => vec![backend, MonitorBackend::Polling],
+      None if cfg!(windows) => vec![
+        MonitorBackend::Etw,
+        MonitorBackend::Wmi,
+        MonitorBackend::Polling,
+      ],
+      None if cfg!(target_os = "linux") => vec![MonitorBackend::Netlink, MonitorBackend::Polling],
+      None => Vec::new(),
+    }
+  }
 }
 
 static PROCESS_MONITOR: LazyLock<Mutex<Option<Box<dyn WinProcessMonitor + Send>>>> =
   LazyLock::new(|| Mutex::new(None));
 
+/// Task sampling the resources of running game processes, see `RESOURCE_SAMPLING_INTERVAL`
+static RESOURCE_SAMPLER: std::sync::Mutex<Option<tokio::task::JoinHandle<()>>> =
+  std::sync::Mutex::new(None);
+
 pub async fn start_monitoring(
-  local_game_pathes: Vec<String>,
-  local_game_ids: Vec<String>,
+  games: Vec<KnownGame>,
   callback: Option<NapiWeakThreadsafeFunction<ProcessEvent, ()>>,
-) {
+  config: MonitorConfig,
+) -> MonitorStatus {
   let mut guard_monitor = PROCESS_MONITOR.lock().await;
 
   // stop monitoring if already existed
   if let Some(mut monitor) = guard_monitor.take() {
     monitor.stop_monitoring();
   }
+  stop_resource_sampler();
 
   // initialize known games
-  gm::get()
-    .lock()
-    .await
-    .init(local_game_pathes, local_game_ids, callback);
-
-  // initialize a monitor and start monitoring
-  let is_elevated = win32::is_elevated_privilege();
-  let mut monitor: Box<dyn WinProcessMonitor> = if is_elevated {
-    log::info("application is running with elevated privilege, using ETW process monitor");
-    Box::new(EtwMonitor::new())
-  } else {
-    log::info("application is running with normal privilege, using polling monitor");
-    // Box::new(WmiMonitor::new())
-    Box::new(PollingMonitor::new())
+  let update = {
+    let mut gm = gm::get().lock().await;
+    gm.set_resolve_symlinks(config.resolve_symlinks);
+    gm.init(games, callback)
   };
 
-  let result = monitor.start_monitoring();
-  if result.is_err() {
-    log::error("failed to start native monitor");
-    return;
+  // initialize a monitor and start monitoring, falling back to the next backend on failure
+  let mut status = MonitorStatus {
+    backend: None,
+    failures: Vec::new(),
+    errors: update.errors,
+    conflicts: update.conflicts,
+  };
+  for backend in config.fallback_chain() {
+    match start_backend(backend, &config) {
+      Ok(monitor) => {
+        log::info(format!("native monitor is running with {:?} backend", backend).as_str());
+        *guard_monitor = Some(monitor);
+        status.backend = Some(backend);
+        break;
+      }
+      Err(e) => {
+        log::error(format!("failed to start {:?} monitor: {}", backend, e).as_str());
+        status.failures.push(MonitorFailure {
+          backend,
+          reason: e.to_string(),
+        });
+      }
+    }
   }
-
-  *guard_monitor = Some(monitor);
   // drop mutex guard immediately after using to avoid potential dead lock
   drop(guard_monitor);
 
-  // fire and forget a background concurrency to check games at startup
-  if is_elevated {
-    tokio::spawn(async {
-      tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+  let Some(backend) = status.backend else {
+    log::error("failed to start native monitor");
+    return status;
+  };
+
+  // fire and forget a background concurrency to check games at startup,
+  // event driven monitors only report processes started after subscription,
+  // while the polling monitor reports every running process on its first poll with a startup scan
+  if config.startup_scan && backend != MonitorBackend::Polling {
+    let delay = config.startup_scan_delay;
+    tokio::spawn(async move {
+      tokio::time::sleep(delay).await;
       startup_process_check().await;
     });
   }
+
+  // the ETW monitor reports the resources of a process with its exit
+  if backend != MonitorBackend::Etw {
+    let sampler = tokio::spawn(async {
+      let mut interval = tokio::time::interval(RESOURCE_SAMPLING_INTERVAL);
+      loop {
+        interval.tick().await;
+        gm::get().lock().await.sample_resources();
+      }
+    });
+    if let Ok(mut guard) = RESOURCE_SAMPLER.lock() {
+      *guard = Some(sampler);
+    }
+  }
+  status
+}
+
+/// Create a monitor of the given backend and start monitoring
+fn start_backend(
+  backend: MonitorBackend,
+  config: &MonitorConfig,
+) -> std::io::Result<Box<dyn WinProcessMonitor>> {
+  let mut monitor: Box<dyn WinProcessMonitor> = match backend {
+    #[cfg(windows)]
+    MonitorBackend::Etw => {
+      // starting a trace session requires elevated privilege, fail early with a clear reason
+      if !platform::is_elevated_privilege() {
+        return Err(std::io::Error::new(
+          std::io::ErrorKind::PermissionDenied,
+          "application is running with normal privilege",
+        ));
+      }
+      Box::new(EtwMonitor::new())
+    }
+    #[cfg(windows)]
+    MonitorBackend::Wmi => Box::new(WmiMonitor::new()),
+    #[cfg(target_os = "linux")]
+    MonitorBackend::Netlink => Box::new(NetlinkMonitor::new()),
+    MonitorBackend::Polling => Box::new(PollingMonitor::new(
+      config.polling_interval,
+      config.startup_scan,
+    )),
+    #[allow(unreachable_patterns)]
+    _ => {
+      return Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("{:?} monitor is not available on this platform", backend),
+      ))
+    }
+  };
+  monitor.start_monitoring()?;
+  Ok(monitor)
+}
+
+fn stop_resource_sampler() {
+  if let Some(sampler) = RESOURCE_SAMPLER
+    .lock()
+    .ok()
+    .and_then(|mut guard| guard.take())
+  {
+    sampler.abort();
+  }
 }
 
 pub async fn stop_monitoring() {
+  stop_resource_sampler();
   if let Some(mut monitor) = PROCESS_MONITOR.lock().await.take() {
     monitor.stop_monitoring();
     // yield back control to tokio runtime to do cleanups (...or wait for a short duration?)
@@ -91,25 +367,64 @@ pub async fn stop_monitoring() {
   }
 }
 
-pub async fn add_known_game(path: String, id: String) {
-  gm::get().lock().await.add_known_game(path, id);
+pub async fn add_known_game(game: KnownGame) -> KnownGameUpdate {
+  let update = gm::get().lock().await.add_known_game(game);
+  track_known_game_processes(update).await
 }
 
 pub async fn remove_known_game_by_id(game_id: String, preserve_running_process: bool) {
-  gm::get().lock().await.remove_known_game_by_id(&game_id, preserve_running_process);
-}
-
-pub async fn replace_known_games(local_game_pathes: Vec<String>, local_game_ids: Vec<String>) {
   gm::get()
     .lock()
     .await
-    .init_known_games(local_game_pathes, local_game_ids);
+    .remove_known_game_by_id(&game_id, preserve_running_process);
+}
+
+pub async fn replace_known_games(games: Vec<KnownGame>) -> KnownGameUpdate {
+  let update = gm::get().lock().await.replace_known_games(games);
+  track_known_game_processes(update).await
+}
+
+pub async fn patch_known_games(patch: KnownGamePatch) -> KnownGameUpdate {
+  let update = gm::get().lock().await.patch_known_games(patch);
+  track_known_game_processes(update).await
 }
 
 pub async fn is_running(path: String, is_folder: Option<bool>) -> bool {
   gm::get().lock().await.is_running(path, is_folder)
 }
 
+pub async fn get_session(game_id: String) -> Option<SessionSummary> {
+  gm::get().lock().await.get_session_summary(&game_id)
+}
+
+pub async fn get_game_pids(game_id: String) -> Vec<u32> {
+  gm::get().lock().await.get_game_pids(&game_id)
+}
+
+pub async fn set_session_grace_period(game_id: Option<String>, grace_period: Duration) {
+  gm::get()
+    .lock()
+    .await
+    .set_grace_period(game_id, grace_period);
+}
+
+/// Compile and replace the matching rules of all games, reporting every invalid pattern
+pub async fn set_matching_rules(rules: Vec<MatchingRule>) -> Result<(), Vec<RuleError>> {
+  let mut compiled = Vec::with_capacity(rules.len());
+  let mut errors = Vec::new();
+  for rule in rules {
+    match CompiledRule::compile(rule) {
+      Ok(rule) => compiled.push(rule),
+      Err(e) => errors.extend(e),
+    }
+  }
+  if !errors.is_empty() {
+    return Err(errors);
+  }
+  gm::get().lock().await.set_matching_rules(compiled);
+  Ok(())
+}
+
 pub async fn manual_update_process_status() {
   if let Some(monitor) = PROCESS_MONITOR.lock().await.as_mut() {
     monitor.manual_update_process_status();
@@ -118,13 +433,23 @@ pub async fn manual_update_process_status() {
 
 /// Check if there are any known games already running at startup
 async fn startup_process_check() {
-  let all_process = win32::get_all_process();
-  let mut gm_guard = gm::get().lock().await;
-  for proc in all_process {
-    gm_guard.handle_process_message(ProcessMessage {
-      pid: proc.pid,
-      status: ProcessStatus::Started,
-      path: proc.full_path,
-    });
+  let all_process = platform::get_all_process();
+  gm::get().lock().await.track_running_processes(all_process);
+}
+
+/// Track running processes of the games which have been added or updated, if monitoring.
+///
+/// Processes already tracked are attributed again by `GameManager`, while processes which now match
+/// a game are only found by scanning, since their start has been reported before.
+async fn track_known_game_processes(mut update: KnownGameUpdate) -> KnownGameUpdate {
+  if update.added.is_empty() && update.updated.is_empty() {
+    return update;
+  }
+  if PROCESS_MONITOR.lock().await.is_none() {
+    return update;
   }
+  let all_process = platform::get_all_process();
+  let tracked = gm::get().lock().await.track_running_processes(all_process);
+  update.reattributed.extend(tracked);
+  update
 }
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use tokio::{sync::mpsc, task};
+
+use super::{gm, ProcessMessage, WinProcessMonitor};
+use crate::{
+  log,
+  platform::linux::{
+    proc_connector::{ProcConnector, ProcEvent},
+    procfs,
+  },
+};
+
+const CHANNEL_SIZE: usize = 256;
+
+/// Real-time process monitor backed by the netlink process connector.
+///
+/// This is the Linux counterpart of the ETW monitor, it requires `CAP_NET_ADMIN`.
+pub struct NetlinkMonitor {
+  connector: Option<ProcConnector>,
+  rx_handle: Option<task::JoinHandle<()>>,
+  term_sender: Option<mpsc::Sender<()>>,
+}
+
+impl NetlinkMonitor {
+  pub fn new() -> Self {
+    Self {
+      connector: None,
+      rx_handle: None,
+      term_sender: None,
+    }
+  }
+
+  /// Convert a connector event to a process message, invoked on the connector consumer thread.
+  ///
+  /// `start_times` remembers the start time of processes seen starting, since it can not be read
+  /// from procfs anymore once a process has exited.
+  fn handle_event(
+    tx: &mpsc::Sender<ProcessMessage>,
+    start_times: &mut HashMap<u32, i64>,
+    event: ProcEvent,
+  ) {
+    let message = match event {
+      ProcEvent::Exec { pid } => {
+        // resolve the path as soon as possible, short-lived processes may have already gone
+        let path = procfs::get_process_full_path_by_pid(pid);
+        if path.is_empty() {
+          return;
+        }
+        let message =
+          ProcessMessage::started(pid, procfs::get_parent_pid(pid), path).query_start_time();
+        if let Some(start_time) = message.details.start_time {
+          start_times.insert(pid, start_time);
+        }
+        message
+      }
+      ProcEvent::Exit { pid, exit_code } => {
+        let message = ProcessMessage::terminated(pid, String::new()).with_exit_code(exit_code);
+        match start_times.remove(&pid) {
+          Some(start_time) => message.with_start_time(start_time),
+          None => message,
+        }
+      }
+    };
+    let pid = message.pid;
+    if let Err(e) = tx.blocking_send(message) {
+      log::error(
+        format!(
+          "failed to send a process information, pid: {}: {:?}",
+          pid, e
+        )
+        .as_str(),
+      );
+    }
+  }
+
+  async fn rx_task(mut rx: mpsc::Receiver<ProcessMessage>, mut t_rx: mpsc::Receiver<()>) {
+    log::info("Netlink monitor rx channel has been spawned");
+    loop {
+      tokio::select! {
+        op_data = rx.recv() => {
+          if let Some(data) = op_data {
+            gm::get().lock().await.handle_process_message(data);
+          } else {
+            log::error("Netlink monitor rx channel accidentally stopped, likely caused by termination of the tx half");
+            break;
+          }
+        }
+        _ = t_rx.recv() => {
+          // termination signal received
+          break;
+        }
+      }
+    }
+  }
+}
+
+impl WinProcessMonitor for NetlinkMonitor {
+  fn start_monitoring(&mut self) -> std::io::Result<()> {
+    log::info("Netlink monitor is starting...");
+    // stop existing connector
+    self.stop_monitoring();
+
+    // termination signal channel
+    let (t_tx, t_rx) = mpsc::channel::<()>(1);
+    // ProcessMessage channel
+    let (tx, rx) = mpsc::channel::<ProcessMessage>(CHANNEL_SIZE);
+
+    // subscribe to process events, fails if we lack the capability to join the multicast group
+    let mut connector = ProcConnector::open()?;
+    let mut start_times = HashMap::new();
+    connector.consume_events(move |event| Self::handle_event(&tx, &mut start_times, event));
+
+    // spawn a rx_task to handle process event message
+    let rx_handle = tokio::spawn(Self::rx_task(rx, t_rx));
+
+    // transfer the ownership of objects to self
+    self.connector = Some(connector);
+    self.rx_handle = Some(rx_handle);
+    self.term_sender = Some(t_tx);
+
+    Ok(())
+  }
+
+  fn stop_monitoring(&mut self) {
+    // send a termination signal to rx task
+    if let Some(t_sender) = self.term_sender.take() {
+      let _ = t_sender.try_send(());
+    }
+
+    if let Some(rx_handle) = self.rx_handle.take() {
+      rx_handle.abort();
+      log::info("Netlink monitor rx channel has been aborted");
+    }
+
+    // unsubscribe and let the consumer thread exit
+    if let Some(mut connector) = self.connector.take() {
+      connector.close();
+    }
+  }
+
+  fn manual_update_process_status(&mut self) {
+    // do nothing since the connector delivers process events in real time
+  }
+}
+
+impl Drop for NetlinkMonitor {
+  fn drop(&mut self) {
+    self.stop_monitoring();
+  }
+}
@@ -1,3 +1,4 @@
+use std::time::Duration;
 use tokio::{
   sync::{broadcast, mpsc},
   task,
@@ -6,11 +7,14 @@ use tokio::{
 use super::{gm, ProcessMessage, WinProcessMonitor};
 use crate::log;
 
-mod polling;
+pub mod polling;
 
 const CHANNEL_SIZE: usize = 256;
 
 pub struct PollingMonitor {
+  interval: Duration,
+  /// Whether the first poll reports the processes which are already running
+  startup_scan: bool,
   tx_handle: Option<task::JoinHandle<()>>,
   rx_handle: Option<task::JoinHandle<()>>,
   term_sender: Option<broadcast::Sender<()>>,
@@ -19,8 +23,10 @@ pub struct PollingMonitor {
 }
 
 impl PollingMonitor {
-  pub fn new() -> Self {
+  pub fn new(interval: Duration, startup_scan: bool) -> Self {
     Self {
+      interval,
+      startup_scan,
       tx_handle: None,
       rx_handle: None,
       term_sender: None,
@@ -29,20 +35,22 @@ impl PollingMonitor {
   }
 
   async fn tx_task(
+    interval: Duration,
+    startup_scan: bool,
     tx: mpsc::Sender<ProcessMessage>,
     mut t_rx: broadcast::Receiver<()>,
     mut m_rx: mpsc::Receiver<()>,
   ) {
     log::info("Polling monitor tx channel has been spawned");
     // create a polling object
-    let mut polling = polling::Polling::new(tx);
+    let mut polling = polling::Polling::new(tx, startup_scan);
     // update process status immediately once thread is spawned
     polling.update_process_status().await;
 
     loop {
       tokio::select! {
-        // polling every 5 seconds
-        _ = tokio::time::sleep(tokio::time::Duration::from_secs(5)) => {
+        // polling at the configured interval
+        _ = tokio::time::sleep(interval) => {
           polling.update_process_status().await;
         }
         // termination signal received
@@ -81,7 +89,7 @@ impl PollingMonitor {
 }
 
 impl WinProcessMonitor for PollingMonitor {
-  fn start_monitoring(&mut self) -> windows_core::Result<()> {
+  fn start_monitoring(&mut self) -> std::io::Result<()> {
     log::info("Polling monitor is starting...");
     // stop existing polling monitor
     self.stop_monitoring();
@@ -99,7 +107,13 @@ impl WinProcessMonitor for PollingMonitor {
     // spawn a rx_task to handle process event message
     let rx_handle = tokio::spawn(Self::rx_task(rx, t_rx));
     // spawn a tx_task to start polling
-    let tx_handle = tokio::spawn(Self::tx_task(tx, t_rx2, m_rx));
+    let tx_handle = tokio::spawn(Self::tx_task(
+      self.interval,
+      self.startup_scan,
+      tx,
+      t_rx2,
+      m_rx,
+    ));
 
     // transfer the ownership of objects to self
     self.rx_handle = Some(rx_handle);
@@ -126,6 +140,7 @@ impl WinProcessMonitor for PollingMonitor {
     }
   }
 
+  #[allow(clippy::option_map_unit_fn)]
   fn manual_update_process_status(&mut self) {
     self.manual_update_sender.as_ref().map(|sender| {
       let _ = sender.try_send(());
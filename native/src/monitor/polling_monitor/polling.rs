@@ -2,26 +2,44 @@ use std::collections::HashMap;
 
 use tokio::sync::mpsc;
 
-use crate::monitor::{ProcessMessage, ProcessStatus};
-use crate::{log, win32};
+use crate::monitor::ProcessMessage;
+use crate::{log, platform};
 
 pub struct Polling {
   tx: mpsc::Sender<ProcessMessage>,
   process_cache: HashMap<u32, ProcessInfo>,
+  /// Whether new processes are reported, the processes found by the first poll are only cached
+  /// without a startup scan
+  report_started: bool,
 }
 
 struct ProcessInfo {
   pid: u32,
   path: String,
+  /// Start time when the process was found, tells it apart from a later process reusing its pid
+  start_time: Option<i64>,
   accessible: bool,
   pinned: bool,
 }
 
+impl ProcessInfo {
+  /// Whether the pid now belongs to another process, i.e. this one exited between two polls
+  fn is_pid_reused(&self) -> bool {
+    let Some(start_time) = self.start_time else {
+      return false;
+    };
+    platform::get_process_start_time(self.pid).is_some_and(|current| current != start_time)
+  }
+}
+
 impl Polling {
-  pub fn new(tx: mpsc::Sender<ProcessMessage>) -> Self {
+  /// With `startup_scan`, the first poll reports every running process as started
+  #[allow(clippy::redundant_field_names)]
+  pub fn new(tx: mpsc::Sender<ProcessMessage>, startup_scan: bool) -> Self {
     Self {
       tx: tx,
       process_cache: HashMap::with_capacity(256usize),
+      report_started: startup_scan,
     }
   }
 
@@ -33,66 +51,48 @@ impl Polling {
     });
 
     // get all processes and update cache, new processes will be marked as pinned, while old processes that are not found will be removed later
-    for pid in win32::get_all_process_pid() {
+    for (pid, ppid) in platform::get_all_process_ppid() {
       if let Some(process) = self.process_cache.get_mut(&pid) {
-        // an existing process is found, mark it as pinned
-        process.pinned = true;
-      } else {
-        // a new process is found, add it to cache and mark it as pinned
-        let path = win32::get_process_full_path_by_pid(pid);
-        let accessible = !path.is_empty();
-        self.process_cache.insert(
-          pid,
-          ProcessInfo {
-            pid: pid,
-            path: path.clone(),
-            accessible: accessible,
-            pinned: true,
-          },
-        );
-        // send process start message if the process is accessible
-        if accessible {
-          match self
-            .tx
-            .send(ProcessMessage {
-              pid: pid,
-              status: ProcessStatus::Started,
-              path: path,
-            })
-            .await
-          {
-            Err(e) => {
-              log::error(format!("failed to send a process info, pid: {}: {:?}", pid, e).as_str());
-            }
-            Ok(()) => {}
-          }
+        // only processes which have been reported can be reused unnoticed
+        if !process.accessible || !process.is_pid_reused() {
+          // an existing process is found, mark it as pinned
+          process.pinned = true;
+          continue;
+        }
+        // the cached process exited and its pid is taken by a new one, report both
+        if let Some(process) = self.process_cache.remove(&pid) {
+          self.send_termination(&process).await;
+        }
+      }
+      // a new process is found, add it to cache and mark it as pinned
+      let path = platform::get_process_full_path_by_pid(pid);
+      let accessible = !path.is_empty();
+      let mut process = ProcessInfo {
+        pid,
+        path: path.clone(),
+        start_time: None,
+        accessible,
+        pinned: true,
+      };
+      // processes running before the first poll are cached with their start time only
+      if accessible && !self.report_started {
+        process.start_time = platform::get_process_start_time(pid);
+      }
+      // send process start message if the process is accessible
+      if accessible && self.report_started {
+        let msg = ProcessMessage::started(pid, ppid, path).query_start_time();
+        process.start_time = msg.details.start_time;
+        if let Err(e) = self.tx.send(msg).await {
+          log::error(format!("failed to send a process info, pid: {}: {:?}", pid, e).as_str());
         }
       }
+      self.process_cache.insert(pid, process);
     }
 
     // send process termination message for unpinned processes
     for (_, process) in self.process_cache.iter() {
       if !process.pinned && process.accessible {
-        match self
-          .tx
-          .send(ProcessMessage {
-            pid: process.pid,
-            status: ProcessStatus::Terminated,
-            path: process.path.clone(),
-          })
-          .await
-        {
-          Err(e) => {
-            log::error(
-              format!(
-                "failed to send a process termination info, pid: {}: {:?}",
-                process.pid, e
-              )
-              .as_str(),
-            );
-          }
-          Ok(()) => {}
-        }
+        self.send_termination(process).await;
       }
     }
 
@@ -100,5 +100,23 @@ impl Polling {
     self
       .process_cache
       .retain(|_, process: &mut ProcessInfo| process.pinned);
+    // processes found from now on have been started since the first poll
+    self.report_started = true;
+  }
+
+  async fn send_termination(&self, process: &ProcessInfo) {
+    let mut msg = ProcessMessage::terminated(process.pid, process.path.clone());
+    if let Some(start_time) = process.start_time {
+      msg = msg.with_start_time(start_time);
+    }
+    if let Err(e) = self.tx.send(msg).await {
+      log::error(
+        format!(
+          "failed to send a process termination info, pid: {}: {:?}",
+          process.pid, e
+        )
+        .as_str(),
+      );
+    }
   }
 }
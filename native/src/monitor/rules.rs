@@ -0,0 +1,185 @@
+use regex::{Regex, RegexBuilder};
+use std::{fmt, time::Duration};
+
+use crate::{napi_monitor::MatchingRule, utils::path::NormalizedPath};
+
+/// A pattern of a matching rule which failed to compile
+#[derive(Debug, PartialEq)]
+pub struct RuleError {
+  pub game_id: String,
+  /// Name of the offending field of `MatchingRule`
+  pub field: &'static str,
+  pub message: String,
+}
+
+impl fmt::Display for RuleError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "invalid {} of game {}: {}",
+      self.field, self.game_id, self.message
+    )
+  }
+}
+
+impl std::error::Error for RuleError {}
+
+/// Matching rule of a known game, compiled once from a `MatchingRule`.
+///
+/// Exe name globs and path prefixes identify additional processes of the game, while the other
+/// criteria filter the processes identified by either the rule or the known game path.
+pub struct CompiledRule {
+  game_id: String,
+  exe_names: Vec<Regex>,
+  path_prefixes: Vec<NormalizedPath>,
+  cmdline: Option<Regex>,
+  parent_exe_names: Vec<Regex>,
+  exclude_exe_names: Vec<Regex>,
+  min_lifetime: Duration,
+}
+
+impl CompiledRule {
+  /// Compile all patterns of a rule, reporting every invalid one
+  pub fn compile(rule: MatchingRule) -> Result<Self, Vec<RuleError>> {
+    let mut errors = Vec::new();
+    let game_id = rule.game_id;
+    let mut globs = |field: &'static str, patterns: Option<Vec<String>>| -> Vec<Regex> {
+      patterns
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|pattern| match compile_glob(pattern) {
+          Ok(re) => Some(re),
+          Err(message) => {
+            errors.push(RuleError {
+              game_id: game_id.clone(),
+              field,
+              message,
+            });
+            None
+          }
+        })
+        .collect()
+    };
+    let exe_names = globs("exeNames", rule.exe_names);
+    let parent_exe_names = globs("parentExeNames", rule.parent_exe_names);
+    let exclude_exe_names = globs("excludeExeNames", rule.exclude_exe_names);
+
+    let mut path_prefixes = Vec::new();
+    for prefix in rule.path_prefixes.unwrap_or_default() {
+      if prefix.trim().is_empty() {
+        errors.push(RuleError {
+          game_id: game_id.clone(),
+          field: "pathPrefixes",
+          message: "path prefix is empty".to_string(),
+        });
+        continue;
+      }
+      path_prefixes.push(NormalizedPath::new(prefix));
+    }
+
+    let cmdline = match rule.cmdline_pattern.map(|pattern| Regex::new(&pattern)) {
+      Some(Ok(re)) => Some(re),
+      Some(Err(e)) => {
+        errors.push(RuleError {
+          game_id: game_id.clone(),
+          field: "cmdlinePattern",
+          message: e.to_string(),
+        });
+        None
+      }
+      None => None,
+    };
+
+    if !errors.is_empty() {
+      return Err(errors);
+    }
+    Ok(Self {
+      game_id,
+      exe_names,
+      path_prefixes,
+      cmdline,
+      parent_exe_names,
+      exclude_exe_names,
+      min_lifetime: Duration::from_millis(rule.min_lifetime.unwrap_or(0).into()),
+    })
+  }
+
+  pub fn game_id(&self) -> &str {
+    &self.game_id
+  }
+
+  /// Whether a process is identified as the game by its exe name or directory
+  pub fn identifies(&self, l_path: &NormalizedPath) -> bool {
+    self
+      .exe_names
+      .iter()
+      .any(|re| re.is_match(l_path.file_name()))
+      || self
+        .path_prefixes
+        .iter()
+        .any(|prefix| l_path.is_under(prefix))
+  }
+
+  /// Whether a process is never attributed to the game, e.g. a crash handler
+  pub fn excludes(&self, l_path: &NormalizedPath) -> bool {
+    self
+      .exclude_exe_names
+      .iter()
+      .any(|re| re.is_match(l_path.file_name()))
+  }
+
+  /// Whether an identified process meets the command line and parent process requirements.
+  ///
+  /// `cmdline` is only invoked if a command line pattern is required, it returns the command line
+  /// of the process. `parent_paths` is only invoked if a parent process is required, it returns the
+  /// paths of the parent process, including its alternative paths.
+  pub fn accepts(
+    &self,
+    cmdline: impl FnOnce() -> Option<String>,
+    parent_paths: impl FnOnce() -> Vec<NormalizedPath>,
+  ) -> bool {
+    if let Some(re) = &self.cmdline {
+      // the command line of an inaccessible process is unknown, it can not meet the requirement
+      if !cmdline().is_some_and(|cmdline| re.is_match(&cmdline)) {
+        return false;
+      }
+    }
+    if self.parent_exe_names.is_empty() {
+      return true;
+    }
+    parent_paths().iter().any(|path| {
+      self
+        .parent_exe_names
+        .iter()
+        .any(|re| re.is_match(path.file_name()))
+    })
+  }
+
+  /// How long a process must live before it is attributed to the game
+  pub fn min_lifetime(&self) -> Duration {
+    self.min_lifetime
+  }
+}
+
+/// Compile a case insensitive glob which matches a whole exe name, `*` and `?` are supported
+pub fn compile_glob(pattern: &str) -> Result<Regex, String> {
+  if pattern.is_empty() {
+    return Err("glob is empty".to_string());
+  }
+  if pattern.contains(['/', '\\']) {
+    return Err(format!("glob {pattern} must not contain path separators"));
+  }
+  let mut source = String::from("^");
+  for c in pattern.chars() {
+    match c {
+      '*' => source.push_str(".*"),
+      '?' => source.push('.'),
+      c => source.push_str(&regex::escape(&c.to_string())),
+    }
+  }
+  source.push('$');
+  RegexBuilder::new(&source)
+    .case_insensitive(true)
+    .build()
+    .map_err(|e| e.to_string())
+}
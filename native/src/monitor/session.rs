@@ -0,0 +1,165 @@
+use std::{
+  collections::HashSet,
+  sync::atomic::{AtomicU64, Ordering},
+  time::{Duration, Instant},
+};
+
+use crate::{
+  monitor::gm::KnownGameProcessInfo, napi_monitor::SessionSummary, platform::ProcessResources,
+};
+
+/// Distinguishes sessions started within the same millisecond
+static SESSION_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// A play session of a known game.
+///
+/// A session starts with the first process of a game and ends when its last process exits,
+/// no matter how many processes the game spawns in between.
+pub struct GameSession {
+  session_id: String,
+  game_id: String,
+  /// Start time in milliseconds since UNIX epoch
+  start_time: i64,
+  /// Processes of the game which are still running
+  live_pids: HashSet<u32>,
+  /// All processes which have been part of this session, in the order they started
+  pids: Vec<u32>,
+  /// Accumulated foreground time, excluding the current foreground period
+  foreground_time: Duration,
+  /// When the game came into foreground, `None` if it is in background
+  foreground_since: Option<Instant>,
+  /// Resources of the exited processes, `None` if none of them is known
+  resources: Option<ProcessResources>,
+  /// Set once the last process exited, while waiting for the game to restart
+  pending_end: Option<PendingEnd>,
+}
+
+/// The last exited process of a session in its grace period
+pub struct PendingEnd {
+  pub process: KnownGameProcessInfo,
+  /// Exit time of the process in milliseconds since UNIX epoch
+  pub end_time: i64,
+  /// Task which ends the session once the grace period expires
+  timer: tokio::task::JoinHandle<()>,
+}
+
+impl PendingEnd {
+  pub fn new(
+    process: KnownGameProcessInfo,
+    end_time: i64,
+    timer: tokio::task::JoinHandle<()>,
+  ) -> Self {
+    Self {
+      process,
+      end_time,
+      timer,
+    }
+  }
+}
+
+impl GameSession {
+  pub fn new(game_id: String) -> Self {
+    let start_time = chrono::Utc::now().timestamp_millis();
+    let sequence = SESSION_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    Self {
+      session_id: format!("{:x}-{:x}", start_time, sequence),
+      game_id,
+      start_time,
+      live_pids: HashSet::new(),
+      pids: Vec::new(),
+      foreground_time: Duration::ZERO,
+      foreground_since: None,
+      resources: None,
+      pending_end: None,
+    }
+  }
+
+  pub fn session_id(&self) -> &str {
+    &self.session_id
+  }
+
+  /// Add a running process, returns `false` if it is already part of this session
+  pub fn add_process(&mut self, pid: u32) -> bool {
+    if !self.live_pids.insert(pid) {
+      return false;
+    }
+    self.pids.push(pid);
+    true
+  }
+
+  /// Remove an exited process, returns `false` if it is not running in this session
+  pub fn remove_process(&mut self, pid: u32) -> bool {
+    self.live_pids.remove(&pid)
+  }
+
+  /// Add the resources of an exited process
+  pub fn add_resources(&mut self, resources: &ProcessResources) {
+    self.resources.get_or_insert_default().accumulate(resources);
+  }
+
+  /// Whether all processes of this session have exited
+  pub fn is_empty(&self) -> bool {
+    self.live_pids.is_empty()
+  }
+
+  /// Whether all processes have exited and the session is waiting for the game to restart
+  pub fn is_ending(&self) -> bool {
+    self.pending_end.is_some()
+  }
+
+  /// Wait for the game to restart before ending the session
+  pub fn begin_grace(&mut self, pending_end: PendingEnd) {
+    self.cancel_grace();
+    self.pending_end = Some(pending_end);
+  }
+
+  /// Resume the session in its grace period, returns `false` if it is not ending
+  pub fn cancel_grace(&mut self) -> bool {
+    match self.pending_end.take() {
+      Some(pending_end) => {
+        pending_end.timer.abort();
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Take the pending end once the grace period expires
+  pub fn take_pending_end(&mut self) -> Option<PendingEnd> {
+    self.pending_end.take()
+  }
+
+  /// Start or stop accumulating foreground time
+  pub fn set_foreground(&mut self, is_foreground: bool) {
+    match (is_foreground, self.foreground_since) {
+      (true, None) => self.foreground_since = Some(Instant::now()),
+      (false, Some(since)) => {
+        self.foreground_time += since.elapsed();
+        self.foreground_since = None;
+      }
+      _ => {}
+    }
+  }
+
+  /// Total foreground time including the current foreground period
+  pub fn foreground_time(&self) -> Duration {
+    self.foreground_time
+      + self
+        .foreground_since
+        .map(|since| since.elapsed())
+        .unwrap_or_default()
+  }
+
+  /// Summarize this session, `end_time` is `None` if the session is still running
+  pub fn summary(&self, end_time: Option<i64>) -> SessionSummary {
+    SessionSummary {
+      session_id: self.session_id.clone(),
+      game_id: self.game_id.clone(),
+      start_time: self.start_time,
+      end_time,
+      foreground_time: self.foreground_time().as_millis() as i64,
+      resources: self.resources.as_ref().map(Into::into),
+      pids: self.pids.clone(),
+    }
+  }
+}
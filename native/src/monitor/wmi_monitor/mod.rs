@@ -1,14 +1,20 @@
+use std::collections::VecDeque;
+
 use tokio::{
   sync::{broadcast, mpsc},
   task,
 };
 
-use crate::monitor::{ProcessMessage, WinProcessMonitor};
+use crate::monitor::{ProcessIdentity, ProcessMessage, ProcessStatus, WinProcessMonitor};
 use crate::{log, monitor::gm};
 
 mod wmi_async;
 
 const CHANNEL_SIZE: usize = 64;
+// how many recent exits are remembered to drop their late creation events
+const RECENT_EXITS_SIZE: usize = 256;
+// maximum time to wait for WMI subscriptions to be set up
+const READY_TIMEOUT_SECS: u64 = 10;
 
 pub struct WmiMonitor {
   /// Process monitor rx half (managed by tokio runtime)
@@ -33,10 +39,16 @@ impl WmiMonitor {
     }
   }
 
-  fn tx_task(t_rx: broadcast::Receiver<()>, tx: mpsc::Sender<ProcessMessage>) {
+  fn tx_task(
+    t_rx: broadcast::Receiver<()>,
+    tx: mpsc::Sender<ProcessMessage>,
+    ready_tx: std::sync::mpsc::Sender<windows_core::Result<()>>,
+  ) {
     log::info("WMI monitor tx channel has been spawned");
     unsafe {
-      if let Err(e) = wmi_async::wmi_event_monitor(t_rx, tx) {
+      if let Err(e) = wmi_async::wmi_event_monitor(t_rx, tx, &ready_tx) {
+        // report the error to `start_monitoring` if the subscriptions are not set up yet
+        let _ = ready_tx.send(Err(e.clone()));
         log::error("WMI monitor tx channel received a WMI error");
         log::error(e.to_string().as_str());
         log::error("WMI monitor tx channel has been accidentally stopped");
@@ -48,6 +60,9 @@ impl WmiMonitor {
 
   async fn rx_task(mut t_rx: broadcast::Receiver<()>, mut rx: mpsc::Receiver<ProcessMessage>) {
     log::info("WMI monitor rx channel has been spawned");
+    // creation and deletion events come from separate subscriptions, so the exit of a short-lived
+    // process may be delivered before its start
+    let mut recent_exits: VecDeque<ProcessIdentity> = VecDeque::with_capacity(RECENT_EXITS_SIZE);
     loop {
       tokio::select! {
         _ = t_rx.recv() => {
@@ -57,7 +72,22 @@ impl WmiMonitor {
         op_data = rx.recv() => {
           if let Some(data) = op_data {
             // log::info(format!("rcv a message: path: {}, pid: {}, status: {:?}", data.path, data.pid, data.status).as_str());
-            gm::get().lock().await.handle_wmi_message(data);
+            let identity = data.identity();
+            match data.status {
+              ProcessStatus::Started => {
+                if let Some(index) = recent_exits.iter().position(|exit| *exit == identity) {
+                  recent_exits.remove(index);
+                  continue;
+                }
+              }
+              ProcessStatus::Terminated => {
+                if recent_exits.len() == RECENT_EXITS_SIZE {
+                  recent_exits.pop_front();
+                }
+                recent_exits.push_back(identity);
+              }
+            }
+            gm::get().lock().await.handle_process_message(data);
           } else {
             log::error("WMI monitor rx channel accidentally stopped, likely caused by termination of the tx half");
             break;
@@ -69,7 +99,7 @@ impl WmiMonitor {
 }
 
 impl WinProcessMonitor for WmiMonitor {
-  fn start_monitoring(&mut self) -> Result<(), windows_core::Error> {
+  fn start_monitoring(&mut self) -> std::io::Result<()> {
     log::info("WMI monitor is starting...");
     // terminate existing monitoring tasks if any
     self.stop_monitoring();
@@ -88,7 +118,33 @@ impl WinProcessMonitor for WmiMonitor {
     // Spawning a blocking thread that managed by tokio runtime by using `tokio::task::spawn_blocking()`
     // also will cause problem, making Node event loop remain alive that prevents application from being
     // terminated completely. This appears to be a bug in NAPI-RS itself.
-    let tx_handle = std::thread::spawn(|| Self::tx_task(t_rx, tx));
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+    let tx_handle = std::thread::spawn(|| Self::tx_task(t_rx, tx, ready_tx));
+
+    // wait until WMI subscriptions are set up so that failures can be reported to the caller,
+    // without stalling other tasks of the runtime meanwhile
+    let wait_ready = || ready_rx.recv_timeout(std::time::Duration::from_secs(READY_TIMEOUT_SECS));
+    let ready = match tokio::runtime::Handle::try_current() {
+      // a current thread runtime, e.g. of tests, has no other worker to hand its tasks to
+      Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+        tokio::task::block_in_place(wait_ready)
+      }
+      _ => wait_ready(),
+    };
+    match ready {
+      Ok(Ok(())) => {}
+      Ok(Err(e)) => {
+        self.stop_monitoring();
+        return Err(e.into());
+      }
+      Err(_) => {
+        self.stop_monitoring();
+        return Err(std::io::Error::new(
+          std::io::ErrorKind::TimedOut,
+          "timed out waiting for WMI event subscriptions",
+        ));
+      }
+    }
 
     // spawn and hold a new task to receive messages
     let rx_handle = tokio::spawn(Self::rx_task(t_rx2, rx));
@@ -112,6 +168,10 @@ impl WinProcessMonitor for WmiMonitor {
       log::info("WMI monitor rx channel has been aborted");
     }
   }
+
+  fn manual_update_process_status(&mut self) {
+    // do nothing since WMI delivers process events by itself
+  }
 }
 
 impl Drop for WmiMonitor {
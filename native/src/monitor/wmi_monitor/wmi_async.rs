@@ -9,16 +9,18 @@ use windows::{
 };
 
 use crate::log;
-use crate::monitor::{ProcessMessage, ProcessStatus};
+use crate::monitor::ProcessMessage;
 
 /// Receive notification from WMI when a process get created or terminated.
 ///
 /// This function will block forever unless it fails or receives a termination signal.
+/// `ready_tx` is notified once the event subscriptions are set up.
 ///
 /// Mostly referenced from: https://learn.microsoft.com/en-us/windows/win32/wmisdk/example--receiving-event-notifications-through-wmi-
 pub unsafe fn wmi_event_monitor(
   mut rx_term: broadcast::Receiver<()>,
   tx: mpsc::Sender<ProcessMessage>,
+  ready_tx: &std::sync::mpsc::Sender<Result<()>>,
 ) -> Result<()> {
   // Step 1: Initialize COM for this thread
   let hres = Com::CoInitializeEx(None, Com::COINIT_MULTITHREADED);
@@ -140,7 +142,7 @@ pub unsafe fn wmi_event_monitor(
   let hr = services.ExecNotificationQueryAsync(
     &BSTR::from("WQL"),
     &BSTR::from(
-      "SELECT TargetInstance.ExecutablePath, TargetInstance.ProcessId FROM __InstanceCreationEvent WITHIN 2 WHERE TargetInstance ISA 'Win32_Process'",
+      "SELECT TargetInstance.ExecutablePath, TargetInstance.ProcessId, TargetInstance.ParentProcessId, TargetInstance.CreationDate FROM __InstanceCreationEvent WITHIN 2 WHERE TargetInstance ISA 'Win32_Process'",
     ),
     Wmi::WBEM_FLAG_SEND_STATUS,
     None,
@@ -178,7 +180,7 @@ pub unsafe fn wmi_event_monitor(
   let hr = services.ExecNotificationQueryAsync(
     &BSTR::from("WQL"),
     &BSTR::from(
-      "SELECT TargetInstance.ExecutablePath, TargetInstance.ProcessId FROM __InstanceDeletionEvent WITHIN 2 WHERE TargetInstance ISA 'Win32_Process'",
+      "SELECT TargetInstance.ExecutablePath, TargetInstance.ProcessId, TargetInstance.CreationDate FROM __InstanceDeletionEvent WITHIN 2 WHERE TargetInstance ISA 'Win32_Process'",
     ),
     Wmi::WBEM_FLAG_SEND_STATUS,
     None,
@@ -197,6 +199,8 @@ pub unsafe fn wmi_event_monitor(
     return hr;
   }
 
+  let _ = ready_tx.send(Ok(()));
+
   // block current thread until receiving a termination signal for graceful shutdown
   let _ = rx_term.blocking_recv();
 
@@ -217,6 +221,7 @@ pub unsafe fn wmi_event_monitor(
 
 /// `unsafe_p_sink` **MUST NOT** be null.
 /// It's the caller's responsibility to allocate and deallocate memory for it.
+#[allow(clippy::redundant_field_names)]
 unsafe fn spawn_sink(
   s_type: SinkType,
   tx: mpsc::Sender<ProcessMessage>,
@@ -382,19 +387,47 @@ impl Wmi::IWbemObjectSink_Impl for ProcessSink_Impl {
           continue;
         }
         let pid = v_pid.Anonymous.Anonymous.Anonymous.uintVal;
+
+        // get ParentProcessId, which is only selected by the process creation query
+        let mut ppid = 0u32;
+        if let SinkType::ProcessStart = self.s_type {
+          let mut v_ppid = Variant::VariantInit();
+          let mut cim_type = Wmi::CIM_EMPTY;
+          let result = (*apobject2).Get(
+            &BSTR::from("ParentProcessId"),
+            0,
+            &mut v_ppid,
+            Some(&mut cim_type as *mut _ as *mut i32),
+            None,
+          );
+          if result.is_ok() {
+            ppid = v_ppid.Anonymous.Anonymous.Anonymous.uintVal;
+          }
+          let _ = Variant::VariantClear(&mut v_ppid);
+        }
+        // get CreationDate, which tells the process apart from a later one reusing its pid
+        let mut start_time = 0i64;
+        let mut v_creation = Variant::VariantInit();
+        let mut cim_type = Wmi::CIM_EMPTY;
+        let result = (*apobject2).Get(
+          &BSTR::from("CreationDate"),
+          0,
+          &mut v_creation,
+          Some(&mut cim_type as *mut _ as *mut i32),
+          None,
+        );
+        if result.is_ok() {
+          let creation = v_creation.Anonymous.Anonymous.Anonymous.bstrVal.to_string();
+          start_time = parse_cim_datetime(&creation).unwrap_or(0);
+        }
+        let _ = Variant::VariantClear(&mut v_creation);
+
         // check sink type and send corresponding message
         let msg = match self.s_type {
-          SinkType::ProcessStart => ProcessMessage {
-            path: path,
-            pid: pid,
-            status: ProcessStatus::Started,
-          },
-          SinkType::ProcessStop => ProcessMessage {
-            path: path,
-            pid: pid,
-            status: ProcessStatus::Terminated,
-          },
-        };
+          SinkType::ProcessStart => ProcessMessage::started(pid, ppid, path),
+          SinkType::ProcessStop => ProcessMessage::terminated(pid, path),
+        }
+        .with_start_time(start_time);
         match self.tx.try_send(msg) {
           Ok(_) => {}
           Err(TrySendError::Closed(_)) => {
@@ -427,3 +460,12 @@ impl Wmi::IWbemObjectSink_Impl for ProcessSink_Impl {
     Ok(())
   }
 }
+
+/// Parse a CIM_DATETIME value, e.g. `20240131235959.123456+480`, to milliseconds since UNIX epoch.
+///
+/// The offset is the number of minutes from UTC.
+fn parse_cim_datetime(value: &str) -> Option<i64> {
+  let local = chrono::NaiveDateTime::parse_from_str(value.get(..21)?, "%Y%m%d%H%M%S%.6f").ok()?;
+  let offset: i64 = value.get(21..)?.parse().ok()?;
+  Some(local.and_utc().timestamp_millis() - offset * 60 * 1000)
+}
@@ -8,8 +8,9 @@ use crate::foreground;
 pub async fn install_foreground_hook(
   callback: Option<ThreadsafeFunction<String, (), String, Status, true, true>>,
   wait_time: Option<u32>,
-) {
-  foreground::install_hook(callback, wait_time).await;
+) -> napi::Result<()> {
+  foreground::install_hook(callback, wait_time).await?;
+  Ok(())
 }
 
 #[napi(js_name = "uninstallForegroundHook")]
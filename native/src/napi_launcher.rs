@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use napi_derive::napi;
+
+use crate::launcher;
+
+/// How to launch the executable of a game
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+  /// Full path of the executable
+  pub path: String,
+  pub args: Option<Vec<String>>,
+  /// Working directory, defaults to the folder of the executable
+  pub cwd: Option<String>,
+  /// Variables added to the environment of the application
+  pub env: Option<HashMap<String, String>>,
+  /// Also track the processes started by the launched process before it exits, defaults to `false`
+  pub track_tree: Option<bool>,
+  /// Contain the launched process and all its descendants in a Job Object on Windows, or on Linux
+  /// in a cgroup if one can be created and otherwise in a process tree kept by child subreapers,
+  /// so that the game runs until all of them exit, defaults to `true`
+  pub contain: Option<bool>,
+  /// How long in milliseconds a failing exit is reported as a launch failure, defaults to 2000,
+  /// 0 returns right after spawning
+  pub early_exit_window: Option<u32>,
+}
+
+/// Reasons a game can not be launched
+#[napi(string_enum = "kebab-case")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LaunchErrorKind {
+  /// The executable does not exist
+  NotFound,
+  /// The executable or its working directory is not accessible
+  PermissionDenied,
+  /// The executable requires administrator rights, it has to be launched through the shell to show
+  /// the elevation prompt
+  ElevationRequired,
+  /// The process could not be created for another reason
+  SpawnFailed,
+  /// The process exited with a failure right after launch
+  EarlyExit,
+}
+
+/// A game which could not be launched
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaunchError {
+  pub kind: LaunchErrorKind,
+  pub message: String,
+  /// Exit code of an early exit
+  pub exit_code: Option<i64>,
+}
+
+/// Result of launching a game, either `pid` or `error` is set
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct LaunchResult {
+  pub pid: Option<u32>,
+  pub error: Option<LaunchError>,
+}
+
+/// Launch the executable of a game, its process is tracked as the game whatever its path is
+#[napi(js_name = "launchGame")]
+pub async fn launch_game(id: String, options: LaunchOptions) -> LaunchResult {
+  match launcher::launch_game(id, options).await {
+    Ok(pid) => LaunchResult {
+      pid: Some(pid),
+      error: None,
+    },
+    Err(error) => LaunchResult {
+      pid: None,
+      error: Some(error),
+    },
+  }
+}
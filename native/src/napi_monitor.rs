@@ -1,13 +1,74 @@
+use std::time::Duration;
+
 use napi::bindgen_prelude::Status;
 use napi::threadsafe_function::ThreadsafeFunction;
 use napi_derive::napi;
 
-use crate::monitor;
+use crate::{monitor, platform::ProcessResources};
 
 #[napi]
 pub enum ProcessEventType {
   Creation,
   Termination,
+  /// The first process of a game started
+  SessionStarted,
+  /// The last process of a game exited
+  SessionEnded,
+  /// A process matches a key shared by several games and is not attributed to any of them,
+  /// see `ProcessEvent.candidates`
+  AmbiguousMatch,
+}
+
+/// A summary of a game session, see `ProcessEventType::SessionStarted` and `SessionEnded`
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+  pub session_id: String,
+  pub game_id: String,
+  /// Start time in milliseconds since UNIX epoch
+  pub start_time: i64,
+  /// End time in milliseconds since UNIX epoch, `undefined` if the session is still running
+  pub end_time: Option<i64>,
+  /// Accumulated time in milliseconds the game has been in foreground
+  pub foreground_time: i64,
+  /// Resources of the processes which have exited so far, `undefined` if none is known
+  pub resources: Option<ResourceSummary>,
+  /// All processes which have been part of the session
+  pub pids: Vec<u32>,
+}
+
+/// Resources consumed by a process or a whole session, fields are `undefined` if they are unknown
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceSummary {
+  /// Peak memory in bytes, the peak commit charge on Windows and the peak resident set on Linux
+  pub peak_memory: Option<i64>,
+  /// CPU cycles spent, only reported on Windows
+  pub cpu_cycles: Option<i64>,
+  /// CPU time in milliseconds, only reported on Linux
+  pub cpu_time: Option<i64>,
+  pub read_operations: Option<i64>,
+  pub write_operations: Option<i64>,
+  pub read_bytes: Option<i64>,
+  pub write_bytes: Option<i64>,
+  /// Page faults which had to read from disk
+  pub hard_faults: Option<i64>,
+}
+
+impl From<&ProcessResources> for ResourceSummary {
+  fn from(resources: &ProcessResources) -> Self {
+    let to_i64 = |value: Option<u64>| value.map(|v| i64::try_from(v).unwrap_or(i64::MAX));
+    Self {
+      peak_memory: to_i64(resources.peak_memory),
+      cpu_cycles: to_i64(resources.cpu_cycles),
+      cpu_time: to_i64(resources.cpu_time),
+      read_operations: to_i64(resources.read_operations),
+      write_operations: to_i64(resources.write_operations),
+      read_bytes: to_i64(resources.read_bytes),
+      write_bytes: to_i64(resources.write_bytes),
+      hard_faults: to_i64(resources.hard_faults),
+    }
+  }
 }
 
 #[napi(object)]
@@ -16,15 +77,242 @@ pub struct ProcessEvent {
   pub full_path: String,
   pub pid: u32,
   pub id: String,
+  /// Full command line the process was launched with
+  pub cmdline: Option<String>,
+  /// Working directory the process was launched in
+  pub cwd: Option<String>,
+  /// Start time of the process in milliseconds since UNIX epoch
+  pub start_time: Option<i64>,
+  /// Exit code of a terminated process, `undefined` if it is running or the code is unknown
+  pub exit_code: Option<i64>,
+  /// Whether a terminated process failed shortly after its session started, see `exitCode`
+  pub crashed: Option<bool>,
+  /// Resources a terminated process consumed, `undefined` if they are unknown
+  pub resources: Option<ResourceSummary>,
+  /// The session the process belongs to
+  pub session: Option<SessionSummary>,
+  /// Games an ambiguous process may belong to, `id` is empty in this case
+  pub candidates: Option<Vec<String>>,
+}
+
+/// A known game key shared by several games, e.g. a bare `game.exe`
+#[napi(object)]
+pub struct KnownGameConflict {
+  /// The normalized key
+  pub key: String,
+  /// Games registered with the key, in the order they were added
+  pub game_ids: Vec<String>,
+}
+
+/// How the paths of a known game are matched with the paths of processes
+#[napi(string_enum = "kebab-case")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KnownGameMatchMode {
+  /// The path is the executable of the game
+  File,
+  /// Executables directly in the folder
+  Folder,
+  /// Executables anywhere below the folder, or the path itself
+  FolderRecursive,
+  /// Executables with the file name of the path, wherever they are
+  ExeName,
+}
+
+/// A game whose processes are tracked
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnownGame {
+  pub id: String,
+  /// Paths of the game, Steam AppId keys such as `steam:570` are also accepted
+  pub paths: Vec<String>,
+  /// Defaults to `exe-name` for bare executable names and `folder-recursive` for paths
+  pub match_mode: Option<KnownGameMatchMode>,
+  /// Disabled games are validated but not tracked, defaults to `true`
+  pub enabled: Option<bool>,
+}
+
+/// A known game which has been rejected
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnownGameError {
+  /// Id of the rejected game, may be empty
+  pub id: String,
+  /// Position of the game in the given list
+  pub index: u32,
+  pub reason: String,
+}
+
+/// Changes of known games applied in one call
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct KnownGamePatch {
+  /// Games to add, or to replace the game with the same id
+  pub upserts: Option<Vec<KnownGame>>,
+  /// Ids of the games to remove, unknown ids are ignored
+  pub removes: Option<Vec<String>>,
+}
+
+/// A running process attributed to another game after known games changed
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnownGameReattribution {
+  pub pid: u32,
+  /// Game the process belonged to, `None` if it was not tracked
+  pub previous_id: Option<String>,
+  /// Game the process belongs to now, `None` if it is no longer tracked
+  pub id: Option<String>,
+}
+
+/// Result of adding, replacing or patching known games
+#[napi(object)]
+#[derive(Default)]
+pub struct KnownGameUpdate {
+  /// Games which have been rejected, the others are applied
+  pub errors: Vec<KnownGameError>,
+  /// Keys of the applied games which are shared by several games
+  pub conflicts: Vec<KnownGameConflict>,
+  /// Ids of the games which have been added
+  pub added: Vec<String>,
+  /// Ids of the games whose paths or match mode have changed
+  pub updated: Vec<String>,
+  /// Ids of the games which have been removed or disabled
+  pub removed: Vec<String>,
+  /// Running processes which have changed their game, their events have been emitted
+  pub reattributed: Vec<KnownGameReattribution>,
+}
+
+/// Process monitor backends
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MonitorBackend {
+  /// Event Tracing for Windows, requires elevated privilege
+  Etw,
+  /// WMI process creation and deletion events
+  Wmi,
+  /// Linux netlink process connector, requires `CAP_NET_ADMIN`
+  Netlink,
+  /// Periodically enumerate all processes
+  Polling,
+}
+
+/// A monitor backend which failed to start
+#[napi(object)]
+pub struct MonitorFailure {
+  pub backend: MonitorBackend,
+  pub reason: String,
+}
+
+/// Result of starting the native monitor
+#[napi(object)]
+pub struct MonitorStatus {
+  /// The backend which is actually running, `undefined` if none of them could be started
+  pub backend: Option<MonitorBackend>,
+  /// Backends tried before the running one, in the order of the fallback chain
+  pub failures: Vec<MonitorFailure>,
+  /// Known games which have been rejected
+  pub errors: Vec<KnownGameError>,
+  /// Known game keys shared by several games
+  pub conflicts: Vec<KnownGameConflict>,
+}
+
+/// Backend selection of `MonitorOptions`
+#[napi(string_enum = "lowercase")]
+pub enum MonitorBackendPreference {
+  /// Walk through the fallback chain of current platform
+  Auto,
+  Etw,
+  Wmi,
+  Netlink,
+  Polling,
+}
+
+#[napi(object)]
+pub struct MonitorOptions {
+  /// Defaults to `auto`, a selected backend falls back to polling if it fails to start
+  pub backend: Option<MonitorBackendPreference>,
+  /// Interval of the polling monitor in milliseconds, defaults to 5000
+  pub polling_interval: Option<u32>,
+  /// Whether to check already running games once the monitor is started, defaults to `true`.
+  /// Without it the polling monitor does not report the processes found by its first poll.
+  pub startup_scan: Option<bool>,
+  /// Delay of the startup check in milliseconds, defaults to 10000
+  pub startup_scan_delay: Option<u32>,
+  /// Whether to also match known game paths with symlinks and junctions resolved, defaults to `true`
+  pub resolve_symlinks: Option<bool>,
+}
+
+/// Declarative rules of how processes are attributed to a known game
+#[napi(object)]
+pub struct MatchingRule {
+  pub game_id: String,
+  /// Exe name globs identifying processes of the game, e.g. `game*.exe`
+  pub exe_names: Option<Vec<String>>,
+  /// Directories whose processes are identified as the game
+  pub path_prefixes: Option<Vec<String>>,
+  /// A regex the command line of an identified process must match
+  pub cmdline_pattern: Option<String>,
+  /// Exe name globs, the parent of an identified process must match one of them
+  pub parent_exe_names: Option<Vec<String>>,
+  /// Exe name globs of processes never attributed to the game, e.g. crash handlers
+  pub exclude_exe_names: Option<Vec<String>>,
+  /// Milliseconds a process must live before it is attributed to the game
+  pub min_lifetime: Option<u32>,
+}
+
+/// Lower bound of `MonitorOptions.pollingInterval`, enumerating all processes is not cheap
+const MIN_POLLING_INTERVAL_MS: u32 = 500;
+
+impl TryFrom<MonitorOptions> for monitor::MonitorConfig {
+  type Error = napi::Error;
+
+  fn try_from(options: MonitorOptions) -> napi::Result<Self> {
+    let backend = match options.backend.unwrap_or(MonitorBackendPreference::Auto) {
+      MonitorBackendPreference::Auto => None,
+      MonitorBackendPreference::Etw => Some(MonitorBackend::Etw),
+      MonitorBackendPreference::Wmi => Some(MonitorBackend::Wmi),
+      MonitorBackendPreference::Netlink => Some(MonitorBackend::Netlink),
+      MonitorBackendPreference::Polling => Some(MonitorBackend::Polling),
+    };
+    let mut config = Self {
+      backend,
+      ..Self::default()
+    };
+    if let Some(interval) = options.polling_interval {
+      if interval < MIN_POLLING_INTERVAL_MS {
+        return Err(napi::Error::new(
+          Status::InvalidArg,
+          format!(
+            "pollingInterval must be at least {} ms, got {}",
+            MIN_POLLING_INTERVAL_MS, interval
+          ),
+        ));
+      }
+      config.polling_interval = Duration::from_millis(interval.into());
+    }
+    if let Some(startup_scan) = options.startup_scan {
+      config.startup_scan = startup_scan;
+    }
+    if let Some(delay) = options.startup_scan_delay {
+      config.startup_scan_delay = Duration::from_millis(delay.into());
+    }
+    if let Some(resolve_symlinks) = options.resolve_symlinks {
+      config.resolve_symlinks = resolve_symlinks;
+    }
+    Ok(config)
+  }
 }
 
 #[napi(js_name = "startMonitoring")]
 pub async fn start_monitoring(
-  local_game_pathes: Vec<String>,
-  local_game_ids: Vec<String>,
+  games: Vec<KnownGame>,
   callback: Option<ThreadsafeFunction<ProcessEvent, (), ProcessEvent, Status, true, true>>,
-) {
-  monitor::start_monitoring(local_game_pathes, local_game_ids, callback).await;
+  options: Option<MonitorOptions>,
+) -> napi::Result<MonitorStatus> {
+  let config = match options {
+    Some(options) => options.try_into()?,
+    None => monitor::MonitorConfig::default(),
+  };
+  Ok(monitor::start_monitoring(games, callback, config).await)
 }
 
 #[napi(js_name = "stopMonitoring")]
@@ -32,9 +320,10 @@ pub async fn stop_monitoring() {
   monitor::stop_monitoring().await;
 }
 
+/// Add a known game or replace the one with the same id, returns the keys it shares with other games
 #[napi(js_name = "addKnownGame")]
-pub async fn add_known_game(path: String, id: String) {
-  monitor::add_known_game(path, id).await;
+pub async fn add_known_game(game: KnownGame) -> KnownGameUpdate {
+  monitor::add_known_game(game).await
 }
 
 #[napi(js_name = "removeKnownGameById")]
@@ -42,9 +331,16 @@ pub async fn remove_known_game_by_id(game_id: String, preserve_running_process:
   monitor::remove_known_game_by_id(game_id, preserve_running_process.unwrap_or(false)).await;
 }
 
+/// Replace all known games, only the differences to the current games are applied
 #[napi(js_name = "replaceKnownGames")]
-pub async fn replace_known_games(local_game_pathes: Vec<String>, local_game_ids: Vec<String>) {
-  monitor::replace_known_games(local_game_pathes, local_game_ids).await;
+pub async fn replace_known_games(games: Vec<KnownGame>) -> KnownGameUpdate {
+  monitor::replace_known_games(games).await
+}
+
+/// Add, replace and remove known games in one call, running processes are attributed again
+#[napi(js_name = "patchKnownGames")]
+pub async fn patch_known_games(patch: KnownGamePatch) -> KnownGameUpdate {
+  monitor::patch_known_games(patch).await
 }
 
 #[napi(js_name = "isRunning")]
@@ -52,6 +348,36 @@ pub async fn is_running(path: String, is_folder: Option<bool>) -> bool {
   monitor::is_running(path, is_folder).await
 }
 
+/// Get the running session of a game, `undefined` if the game is not running
+#[napi(js_name = "getSession")]
+pub async fn get_session(game_id: String) -> Option<SessionSummary> {
+  monitor::get_session(game_id).await
+}
+
+/// Get the pids of every running process of a game, including the processes contained with a
+/// launched game which are not attributed to it
+#[napi(js_name = "getGamePids")]
+pub async fn get_game_pids(game_id: String) -> Vec<u32> {
+  monitor::get_game_pids(game_id).await
+}
+
+/// Keep the session of a game running for `seconds` after its last process exited,
+/// so that a restart within this window continues the same session.
+/// Sets the default of all games if `gameId` is omitted, 0 ends sessions immediately.
+#[napi(js_name = "setSessionGracePeriod")]
+pub async fn set_session_grace_period(seconds: u32, game_id: Option<String>) {
+  monitor::set_session_grace_period(game_id, Duration::from_secs(seconds.into())).await;
+}
+
+/// Replace the matching rules of all games, nothing is changed if any pattern is invalid
+#[napi(js_name = "setMatchingRules")]
+pub async fn set_matching_rules(rules: Vec<MatchingRule>) -> napi::Result<()> {
+  monitor::set_matching_rules(rules).await.map_err(|errors| {
+    let reasons: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    napi::Error::new(Status::InvalidArg, reasons.join("; "))
+  })
+}
+
 #[napi(js_name = "manualUpdateProcessStatus")]
 pub async fn manual_update_process_status() {
   monitor::manual_update_process_status().await;
@@ -1,21 +1,36 @@
 use napi_derive::napi;
 
-use crate::win32;
+use crate::{
+  log,
+  platform::{self, Notification, PlatformError},
+};
 
 #[napi(object)]
 pub struct ProcessInfo {
   pub pid: u32,
   pub full_path: String,
+  /// 0 if it can not be retrieved
+  pub parent_pid: u32,
+  /// Milliseconds since UNIX epoch, 0 if it can not be retrieved
+  pub start_time: i64,
 }
 
 #[napi(js_name = "getAllProcess")]
 pub fn get_all_process() -> Vec<ProcessInfo> {
-  win32::get_all_process()
+  platform::get_all_process()
+    .into_iter()
+    .map(|proc| ProcessInfo {
+      pid: proc.pid,
+      full_path: proc.path,
+      parent_pid: proc.ppid,
+      start_time: proc.start_time,
+    })
+    .collect()
 }
 
 #[napi(js_name = "isElevatedPrivilege")]
 pub fn is_elevated_privilege() -> bool {
-  win32::is_elevated_privilege()
+  platform::is_elevated_privilege()
 }
 
 #[napi(js_name = "sendSystemNotification")]
@@ -26,6 +41,25 @@ pub fn send_notification(
   line2: Option<String>,
   image_path: Option<String>,
   silent: Option<bool>,
-) {
-  win32::send_notification(app_id, title, line1, line2, image_path, silent);
+) -> napi::Result<()> {
+  let result = platform::send_notification(Notification {
+    app_id,
+    title,
+    line1,
+    line2,
+    image: image_path,
+    silent,
+  });
+  match result {
+    Ok(_) => {
+      log::info("A system notification has been sent.");
+    }
+    Err(err @ PlatformError::Unsupported(_)) => {
+      return Err(err.into());
+    }
+    Err(err) => {
+      log::error(format!("Failed to send system notification: {}", err).as_str());
+    }
+  }
+  Ok(())
 }
@@ -0,0 +1,122 @@
+use std::{
+  ffi::{CStr, CString},
+  fs, io,
+  os::unix::ffi::OsStrExt,
+  path::{Path, PathBuf},
+  sync::atomic::{AtomicU32, Ordering},
+};
+
+/// Number of cgroups created by current process, it keeps their names unique
+static CGROUP_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// A cgroup v2 created below the cgroup of current process. Processes forked by a process in the
+/// cgroup are in the cgroup as well, wherever they are reparented to.
+///
+/// It can only be created if the cgroup of current process is writable, e.g. the scope systemd
+/// delegates to a desktop application. The cgroup is removed once it is dropped, if it is empty.
+pub struct Cgroup {
+  path: PathBuf,
+  /// `cgroup.procs` of the cgroup, ready to be opened after fork
+  procs: CString,
+}
+
+impl Cgroup {
+  /// Create a cgroup for processes of something, e.g. a game id
+  pub fn create(name: &str) -> io::Result<Self> {
+    let unsupported = |what: &str| io::Error::new(io::ErrorKind::Unsupported, what.to_string());
+    let mounts = fs::read_to_string("/proc/self/mounts")?;
+    let root =
+      find_cgroup2_mount(&mounts).ok_or_else(|| unsupported("cgroup v2 is not mounted"))?;
+    let cgroup = fs::read_to_string("/proc/self/cgroup")?;
+    let parent = parse_unified_cgroup(&cgroup)
+      .ok_or_else(|| unsupported("current process is not in a cgroup v2"))?;
+    let path = Path::new(root)
+      .join(parent.trim_start_matches('/'))
+      .join(cgroup_name(
+        name,
+        CGROUP_COUNT.fetch_add(1, Ordering::Relaxed),
+      ));
+    fs::create_dir(&path)?;
+    let procs = CString::new(path.join("cgroup.procs").as_os_str().as_bytes())?;
+    Ok(Self { path, procs })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  /// `cgroup.procs` of the cgroup, see `enter`
+  pub fn procs(&self) -> &CStr {
+    &self.procs
+  }
+
+  /// Pids of the processes in the cgroup, exited processes are not listed even before they are
+  /// reaped
+  pub fn pids(&self) -> Vec<u32> {
+    fs::read_to_string(self.path.join("cgroup.procs"))
+      .map(|content| {
+        content
+          .lines()
+          .filter_map(|line| line.trim().parse().ok())
+          .collect()
+      })
+      .unwrap_or_default()
+  }
+}
+
+impl Drop for Cgroup {
+  fn drop(&mut self) {
+    // only empty cgroups can be removed, a cgroup which still has processes is left behind
+    let _ = fs::remove_dir(&self.path);
+  }
+}
+
+/// Move current process into the cgroup whose `cgroup.procs` is given.
+///
+/// Only async-signal-safe functions are called, so it can be called between fork and exec.
+pub fn enter(procs: &CStr) -> io::Result<()> {
+  unsafe {
+    let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+    if fd < 0 {
+      return Err(io::Error::last_os_error());
+    }
+    // 0 stands for the writing process
+    let written = libc::write(fd, b"0".as_ptr().cast(), 1);
+    let err = io::Error::last_os_error();
+    libc::close(fd);
+    if written < 0 {
+      return Err(err);
+    }
+  }
+  Ok(())
+}
+
+/// Mount point of the cgroup v2 hierarchy, given `/proc/self/mounts`. It is usually
+/// `/sys/fs/cgroup`, or `/sys/fs/cgroup/unified` next to cgroup v1 hierarchies.
+pub fn find_cgroup2_mount(mounts: &str) -> Option<&str> {
+  mounts.lines().find_map(|line| {
+    let mut fields = line.split_whitespace();
+    let mount_point = fields.nth(1)?;
+    (fields.next()? == "cgroup2").then_some(mount_point)
+  })
+}
+
+/// Path of a cgroup v2 relative to the hierarchy root, given `/proc/<pid>/cgroup`
+pub fn parse_unified_cgroup(content: &str) -> Option<&str> {
+  content.lines().find_map(|line| line.strip_prefix("0::"))
+}
+
+/// Name of a cgroup, characters which may not appear in a file name are replaced
+pub fn cgroup_name(name: &str, index: u32) -> String {
+  let name: String = name
+    .chars()
+    .map(|c| {
+      if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+        c
+      } else {
+        '_'
+      }
+    })
+    .collect();
+  format!("vnite-{}-{}-{}", name, std::process::id(), index)
+}
@@ -0,0 +1,158 @@
+use tokio::{process::Command, sync::mpsc};
+
+use crate::{
+  log,
+  platform::{
+    ForegroundBackend, Notification, NotificationBackend, PlatformError, PrivilegeBackend,
+    ProcessBackend, ProcessDetails, ProcessEntry, ProcessGroup, ProcessImage, ProcessResources,
+    Result,
+  },
+  utils::steam,
+};
+
+pub mod cgroup;
+pub mod proc_connector;
+pub mod procfs;
+pub mod subreaper;
+pub mod wine;
+
+// CAP_NET_ADMIN from <linux/capability.h>, required by the proc connector
+const CAP_NET_ADMIN: u32 = 12;
+
+/// Linux implementation backed by procfs
+pub struct LinuxPlatform;
+
+impl ProcessBackend for LinuxPlatform {
+  fn get_all_process() -> Vec<ProcessEntry> {
+    procfs::get_all_process()
+  }
+
+  fn get_all_process_ppid() -> Vec<(u32, u32)> {
+    procfs::get_all_process_ppid()
+  }
+
+  fn get_process_full_path_by_pid(pid: u32) -> String {
+    procfs::get_process_full_path_by_pid(pid)
+  }
+
+  fn get_process_details(pid: u32) -> ProcessDetails {
+    procfs::get_process_details(pid)
+  }
+
+  fn get_process_start_time(pid: u32) -> Option<i64> {
+    procfs::get_process_start_time(pid)
+  }
+
+  fn get_process_resources(pid: u32) -> Option<ProcessResources> {
+    procfs::get_process_resources(pid)
+  }
+
+  fn resolve_process_image(pid: u32, path: String) -> ProcessImage {
+    // Windows programs run through Wine or Proton report the loader as their image
+    let mut image = wine::resolve_process_image(pid, &path).unwrap_or(ProcessImage {
+      path,
+      aliases: Vec::new(),
+    });
+    // games launched by Steam carry their AppId, either through the launch wrapper or environment
+    let app_id = steam::find_app_id_in_args(&procfs::read_cmdline(pid))
+      .or_else(|| steam::find_app_id_in_environ(&procfs::read_environ(pid)));
+    if let Some(app_id) = app_id {
+      image.aliases.push(steam::app_id_key(app_id));
+    }
+    image
+  }
+
+  fn prepare_process_group(command: &mut Command, name: &str) -> Result<Box<dyn ProcessGroup>> {
+    let cgroup = match cgroup::Cgroup::create(name) {
+      Ok(cgroup) => cgroup,
+      Err(e) => {
+        log::info(
+          format!(
+            "failed to create cgroup for {}, tracking its process tree instead: {}",
+            name, e
+          )
+          .as_str(),
+        );
+        return prepare_process_tree(command);
+      }
+    };
+    let procs = cgroup.procs().to_owned();
+    // enter the cgroup before exec, so that no descendant is forked outside of it
+    unsafe {
+      command.pre_exec(move || {
+        // the process still runs if it can not enter, it is not contained then
+        let _ = cgroup::enter(&procs);
+        Ok(())
+      });
+    }
+    Ok(Box::new(cgroup))
+  }
+}
+
+/// Contain the process spawned with the command in a process tree, see `ProcessTree`
+fn prepare_process_tree(command: &mut Command) -> Result<Box<dyn ProcessGroup>> {
+  let tree = subreaper::ProcessTree::new()
+    .map_err(|e| PlatformError::Os(format!("failed to become a child subreaper: {}", e)))?;
+  // hold the orphans of the process before exec, so that none is reparented to init
+  unsafe {
+    command.pre_exec(|| {
+      // the process still runs if it can not hold them, they are not contained then
+      let _ = subreaper::set_child_subreaper();
+      Ok(())
+    });
+  }
+  Ok(Box::new(tree))
+}
+
+impl ProcessGroup for cgroup::Cgroup {
+  fn attach(&self, pid: u32) -> Result<()> {
+    // the process entered the cgroup by itself
+    if self.pids().contains(&pid) {
+      return Ok(());
+    }
+    Err(PlatformError::Os(format!(
+      "process {} is not in cgroup {}",
+      pid,
+      self.path().display()
+    )))
+  }
+
+  fn pids(&self) -> Vec<u32> {
+    cgroup::Cgroup::pids(self)
+  }
+}
+
+impl ProcessGroup for subreaper::ProcessTree {
+  fn attach(&self, pid: u32) -> Result<()> {
+    subreaper::ProcessTree::attach(self, pid)
+      .map_err(|e| PlatformError::Os(format!("failed to track process {}: {}", pid, e)))
+  }
+
+  fn pids(&self) -> Vec<u32> {
+    subreaper::ProcessTree::pids(self)
+  }
+}
+
+impl PrivilegeBackend for LinuxPlatform {
+  fn is_elevated_privilege() -> Result<bool> {
+    let status = std::fs::read_to_string("/proc/self/status")
+      .map_err(|e| PlatformError::Os(format!("failed to read process status: {}", e)))?;
+    let effective = procfs::parse_effective_capabilities(&status)
+      .ok_or_else(|| PlatformError::Os("failed to parse effective capabilities".to_string()))?;
+    Ok(effective & (1 << CAP_NET_ADMIN) != 0)
+  }
+}
+
+impl NotificationBackend for LinuxPlatform {
+  fn send_notification(_notification: Notification) -> Result<()> {
+    Err(PlatformError::Unsupported("system notification"))
+  }
+}
+
+impl ForegroundBackend for LinuxPlatform {
+  fn install_foreground_hook(_tx: mpsc::Sender<u32>) -> Result<()> {
+    Err(PlatformError::Unsupported("foreground hook"))
+  }
+
+  fn uninstall_foreground_hook() {}
+}
@@ -0,0 +1,272 @@
+use std::{
+  io, mem,
+  os::fd::{AsRawFd, FromRawFd, OwnedFd},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+};
+
+use crate::log;
+
+// Constants from <linux/connector.h> and <linux/cn_proc.h>
+const CN_IDX_PROC: u32 = 0x1;
+const CN_VAL_PROC: u32 = 0x1;
+const PROC_CN_MCAST_LISTEN: u32 = 1;
+const PROC_CN_MCAST_IGNORE: u32 = 2;
+const PROC_EVENT_EXEC: u32 = 0x0000_0002;
+const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+
+// Sizes of `struct nlmsghdr`, `struct cn_msg` and the `struct proc_event` header
+// (what, cpu, timestamp_ns) preceding the `event_data` union
+const NLMSG_HDR_LEN: usize = 16;
+const CN_MSG_LEN: usize = 20;
+const PROC_EVENT_HDR_LEN: usize = 16;
+
+const RECV_BUFFER_SIZE: usize = 4096;
+// the consumer thread wakes up at this interval to check whether it should stop
+const RECV_TIMEOUT_MS: i64 = 500;
+
+/// A process event received from the kernel proc connector
+#[derive(Debug, PartialEq)]
+pub enum ProcEvent {
+  /// A process called `execve`, the pid is the thread group id
+  Exec { pid: u32 },
+  /// The main thread of a process exited, the pid is the thread group id.
+  ///
+  /// The exit code follows the shell convention, 128 plus the signal number if it was killed.
+  Exit { pid: u32, exit_code: i64 },
+}
+
+/// A netlink socket subscribed to the kernel process events multicast group.
+///
+/// Joining the group requires `CAP_NET_ADMIN`, `open()` fails with `EPERM` otherwise.
+pub struct ProcConnector {
+  fd: Arc<OwnedFd>,
+  running: Arc<AtomicBool>,
+  consumer_handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl ProcConnector {
+  /// Open a netlink connector socket and subscribe to process events
+  pub fn open() -> io::Result<Self> {
+    let fd = unsafe {
+      libc::socket(
+        libc::AF_NETLINK,
+        libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+        libc::NETLINK_CONNECTOR,
+      )
+    };
+    if fd < 0 {
+      return Err(io::Error::last_os_error());
+    }
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    // join the proc connector multicast group, the kernel assigns a port id for us
+    let mut addr: libc::sockaddr_nl = unsafe { mem::zeroed() };
+    addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+    addr.nl_groups = CN_IDX_PROC;
+    let result = unsafe {
+      libc::bind(
+        fd.as_raw_fd(),
+        &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+        mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+      )
+    };
+    if result < 0 {
+      return Err(io::Error::last_os_error());
+    }
+
+    // set a receive timeout so that the consumer thread never blocks forever
+    let timeout = libc::timeval {
+      tv_sec: 0,
+      tv_usec: RECV_TIMEOUT_MS * 1000,
+    };
+    let result = unsafe {
+      libc::setsockopt(
+        fd.as_raw_fd(),
+        libc::SOL_SOCKET,
+        libc::SO_RCVTIMEO,
+        &timeout as *const libc::timeval as *const libc::c_void,
+        mem::size_of::<libc::timeval>() as libc::socklen_t,
+      )
+    };
+    if result < 0 {
+      return Err(io::Error::last_os_error());
+    }
+
+    send_control_message(&fd, PROC_CN_MCAST_LISTEN)?;
+
+    Ok(Self {
+      fd: Arc::new(fd),
+      running: Arc::new(AtomicBool::new(false)),
+      consumer_handle: None,
+    })
+  }
+
+  /// Starts to consume events.
+  /// An OS managed thread will be spawned in this function, `handler` is invoked on that thread.
+  pub fn consume_events<F>(&mut self, mut handler: F)
+  where
+    F: FnMut(ProcEvent) + Send + 'static,
+  {
+    // do nothing if the consumer is already running
+    if self.running.swap(true, Ordering::SeqCst) {
+      return;
+    }
+    let fd = self.fd.clone();
+    let running = self.running.clone();
+    self.consumer_handle = Some(std::thread::spawn(move || {
+      let mut buffer = vec![0u8; RECV_BUFFER_SIZE];
+      while running.load(Ordering::SeqCst) {
+        let len = unsafe {
+          libc::recv(
+            fd.as_raw_fd(),
+            buffer.as_mut_ptr() as *mut libc::c_void,
+            buffer.len(),
+            0,
+          )
+        };
+        if len < 0 {
+          let err = io::Error::last_os_error();
+          match err.raw_os_error() {
+            // receive timeout or interrupted, check the running flag again
+            Some(libc::EAGAIN) | Some(libc::EINTR) => continue,
+            // the socket buffer overflowed and some events were dropped, keep going
+            Some(libc::ENOBUFS) => {
+              log::error("netlink proc connector dropped events due to buffer overrun");
+              continue;
+            }
+            _ => {
+              log::error(format!("failed to receive from netlink socket: {}", err).as_str());
+              break;
+            }
+          }
+        }
+        for event in parse_messages(&buffer[..len as usize]) {
+          handler(event);
+        }
+      }
+      running.store(false, Ordering::SeqCst);
+    }));
+  }
+
+  /// Unsubscribe from process events and signal the consumer thread to stop
+  pub fn close(&mut self) {
+    self.running.store(false, Ordering::SeqCst);
+    let _ = send_control_message(&self.fd, PROC_CN_MCAST_IGNORE);
+    // the consumer thread exits within `RECV_TIMEOUT_MS`, no need to wait for it
+    self.consumer_handle.take();
+  }
+}
+
+impl Drop for ProcConnector {
+  fn drop(&mut self) {
+    self.close();
+  }
+}
+
+/// Send a `PROC_CN_MCAST_*` operation to the proc connector
+fn send_control_message(fd: &OwnedFd, op: u32) -> io::Result<()> {
+  let message = build_control_message(op);
+  let result = unsafe {
+    libc::send(
+      fd.as_raw_fd(),
+      message.as_ptr() as *const libc::c_void,
+      message.len(),
+      0,
+    )
+  };
+  if result < 0 {
+    return Err(io::Error::last_os_error());
+  }
+  Ok(())
+}
+
+/// Build a netlink message carrying a `struct cn_msg` with a single `enum proc_cn_mcast_op`
+pub fn build_control_message(op: u32) -> Vec<u8> {
+  let total_len = NLMSG_HDR_LEN + CN_MSG_LEN + mem::size_of::<u32>();
+  let mut message = Vec::with_capacity(total_len);
+  // struct nlmsghdr
+  message.extend_from_slice(&(total_len as u32).to_ne_bytes());
+  message.extend_from_slice(&(libc::NLMSG_DONE as u16).to_ne_bytes());
+  message.extend_from_slice(&0u16.to_ne_bytes()); // flags
+  message.extend_from_slice(&0u32.to_ne_bytes()); // seq
+  message.extend_from_slice(&std::process::id().to_ne_bytes());
+  // struct cn_msg
+  message.extend_from_slice(&CN_IDX_PROC.to_ne_bytes());
+  message.extend_from_slice(&CN_VAL_PROC.to_ne_bytes());
+  message.extend_from_slice(&0u32.to_ne_bytes()); // seq
+  message.extend_from_slice(&0u32.to_ne_bytes()); // ack
+  message.extend_from_slice(&(mem::size_of::<u32>() as u16).to_ne_bytes());
+  message.extend_from_slice(&0u16.to_ne_bytes()); // flags
+  message.extend_from_slice(&op.to_ne_bytes());
+  message
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+  data
+    .get(offset..offset + 4)
+    .map(|bytes| u32::from_ne_bytes(bytes.try_into().unwrap()))
+}
+
+/// Convert a wait status as reported by `exit_proc_event.exit_code` to a shell style exit code
+pub fn wait_status_to_exit_code(status: u32) -> i64 {
+  match status & 0x7f {
+    0 => ((status >> 8) & 0xff).into(),
+    signal => 128 + i64::from(signal),
+  }
+}
+
+/// Parse all process events contained in a datagram received from the proc connector.
+///
+/// Thread level events are dropped, only the thread group (process) is reported.
+pub fn parse_messages(data: &[u8]) -> Vec<ProcEvent> {
+  let mut events = Vec::new();
+  let mut offset = 0usize;
+  while offset + NLMSG_HDR_LEN <= data.len() {
+    let Some(msg_len) = read_u32(data, offset).map(|v| v as usize) else {
+      break;
+    };
+    if msg_len < NLMSG_HDR_LEN || offset + msg_len > data.len() {
+      break;
+    }
+    if let Some(event) = parse_proc_event(&data[offset + NLMSG_HDR_LEN..offset + msg_len]) {
+      events.push(event);
+    }
+    // netlink messages are aligned to 4 bytes
+    offset += (msg_len + 3) & !3;
+  }
+  events
+}
+
+/// Parse a `struct cn_msg` followed by a `struct proc_event`
+fn parse_proc_event(payload: &[u8]) -> Option<ProcEvent> {
+  if read_u32(payload, 0)? != CN_IDX_PROC || read_u32(payload, 4)? != CN_VAL_PROC {
+    return None;
+  }
+  let event = payload.get(CN_MSG_LEN..)?;
+  let what = read_u32(event, 0)?;
+  let data = PROC_EVENT_HDR_LEN;
+  match what {
+    PROC_EVENT_EXEC => {
+      // struct exec_proc_event { process_pid, process_tgid }
+      let tgid = read_u32(event, data + 4)?;
+      Some(ProcEvent::Exec { pid: tgid })
+    }
+    PROC_EVENT_EXIT => {
+      // struct exit_proc_event { process_pid, process_tgid, exit_code, exit_signal, ... }
+      let pid = read_u32(event, data)?;
+      let tgid = read_u32(event, data + 4)?;
+      if pid != tgid {
+        return None;
+      }
+      let exit_code = wait_status_to_exit_code(read_u32(event, data + 8)?);
+      Some(ProcEvent::Exit {
+        pid: tgid,
+        exit_code,
+      })
+    }
+    _ => None,
+  }
+}
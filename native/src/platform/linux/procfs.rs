@@ -0,0 +1,305 @@
+use std::{collections::HashMap, fs, path::PathBuf, sync::OnceLock};
+
+use crate::platform::{ProcessDetails, ProcessEntry, ProcessResources};
+
+/// Fields of `/proc/<pid>/stat` that we are interested in
+#[derive(Debug, PartialEq)]
+pub struct ProcStat {
+  /// Executable name truncated to 15 bytes by the kernel
+  pub comm: String,
+  /// State like `R` running, `S` sleeping or `Z` zombie
+  pub state: char,
+  pub ppid: u32,
+  /// Page faults which had to read from disk
+  pub major_faults: u64,
+  /// CPU time in clock ticks spent in user mode
+  pub user_ticks: u64,
+  /// CPU time in clock ticks spent in kernel mode
+  pub system_ticks: u64,
+  /// Process start time in clock ticks since system boot
+  pub start_ticks: u64,
+}
+
+/// Fields of `/proc/<pid>/io` that we are interested in
+#[derive(Debug, PartialEq)]
+pub struct ProcIo {
+  /// Read syscalls
+  pub read_operations: u64,
+  /// Write syscalls
+  pub write_operations: u64,
+  /// Bytes fetched from storage
+  pub read_bytes: u64,
+  /// Bytes sent to storage
+  pub write_bytes: u64,
+}
+
+// (boot time in seconds since UNIX epoch, clock ticks per second)
+static CLOCK: OnceLock<(u64, u64)> = OnceLock::new();
+
+/// Parse the content of `/proc/<pid>/stat`.
+///
+/// The `comm` field is wrapped in parentheses and may contain whitespaces or even parentheses,
+/// so the remaining fields are located from the last ')' instead of splitting naively.
+/// See `man 5 proc` for the full layout.
+pub fn parse_stat(content: &str) -> Option<ProcStat> {
+  let (head, rest) = content.rsplit_once(')')?;
+  let (_, comm) = head.split_once('(')?;
+  // fields are numbered from (1) pid, the first one after comm is (3) state
+  let fields: Vec<&str> = rest.split_whitespace().collect();
+  let field = |number: usize| fields.get(number - 3)?.parse().ok();
+  Some(ProcStat {
+    comm: comm.to_string(),
+    state: fields.first()?.chars().next()?,
+    ppid: field(4)? as u32,
+    major_faults: field(12)?,
+    user_ticks: field(14)?,
+    system_ticks: field(15)?,
+    start_ticks: field(22)?,
+  })
+}
+
+/// Parse the content of `/proc/<pid>/io`
+pub fn parse_io(content: &str) -> Option<ProcIo> {
+  let field = |name: &str| {
+    content
+      .lines()
+      .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+      .and_then(|v| v.trim().parse().ok())
+  };
+  Some(ProcIo {
+    read_operations: field("syscr")?,
+    write_operations: field("syscw")?,
+    read_bytes: field("read_bytes")?,
+    write_bytes: field("write_bytes")?,
+  })
+}
+
+/// Parse the peak resident set size (`VmHWM`) in bytes from `/proc/<pid>/status`,
+/// `None` once the process released its memory on exit
+pub fn parse_peak_resident_size(content: &str) -> Option<u64> {
+  content
+    .lines()
+    .find_map(|line| line.strip_prefix("VmHWM:"))
+    .and_then(|v| v.trim().strip_suffix("kB"))
+    .and_then(|v| v.trim().parse::<u64>().ok())
+    .map(|kilobytes| kilobytes * 1024)
+}
+
+/// Split a NUL separated procfs file (`cmdline`, `environ`) into strings
+pub fn parse_nul_separated(content: &[u8]) -> Vec<String> {
+  content
+    .split(|&b| b == 0)
+    .filter(|s| !s.is_empty())
+    .map(|s| String::from_utf8_lossy(s).into_owned())
+    .collect()
+}
+
+/// Join command line arguments into a single line, quoting arguments which contain whitespaces
+pub fn join_cmdline(args: &[String]) -> String {
+  args
+    .iter()
+    .map(|arg| {
+      if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"') {
+        return arg.clone();
+      }
+      format!("\"{}\"", arg.replace('"', "\\\""))
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Parse the content of `/proc/<pid>/environ` into a key value map
+pub fn parse_environ(content: &[u8]) -> HashMap<String, String> {
+  parse_nul_separated(content)
+    .into_iter()
+    .filter_map(|entry| {
+      let (k, v) = entry.split_once('=')?;
+      Some((k.to_string(), v.to_string()))
+    })
+    .collect()
+}
+
+/// Parse the `btime` (boot time in seconds since UNIX epoch) line from `/proc/stat`
+pub fn parse_boot_time(content: &str) -> Option<u64> {
+  content
+    .lines()
+    .find_map(|line| line.strip_prefix("btime "))
+    .and_then(|v| v.trim().parse().ok())
+}
+
+/// Parse the effective capability set (`CapEff`) from `/proc/<pid>/status`
+pub fn parse_effective_capabilities(content: &str) -> Option<u64> {
+  content
+    .lines()
+    .find_map(|line| line.strip_prefix("CapEff:"))
+    .and_then(|v| u64::from_str_radix(v.trim(), 16).ok())
+}
+
+/// Convert a start time in clock ticks since boot to milliseconds since UNIX epoch
+pub fn ticks_to_unix_millis(start_ticks: u64, boot_time: u64, ticks_per_second: u64) -> i64 {
+  if boot_time == 0 || ticks_per_second == 0 {
+    return 0;
+  }
+  (boot_time * 1000 + start_ticks * 1000 / ticks_per_second) as i64
+}
+
+fn clock() -> (u64, u64) {
+  *CLOCK.get_or_init(|| {
+    let boot_time = fs::read_to_string("/proc/stat")
+      .ok()
+      .and_then(|content| parse_boot_time(&content))
+      .unwrap_or(0);
+    let ticks_per_second = match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+      v if v > 0 => v as u64,
+      _ => 100,
+    };
+    (boot_time, ticks_per_second)
+  })
+}
+
+pub fn read_stat(pid: u32) -> Option<ProcStat> {
+  fs::read_to_string(format!("/proc/{pid}/stat"))
+    .ok()
+    .and_then(|content| parse_stat(&content))
+}
+
+/// Get all process information (pid, ppid, full path, start time)
+pub fn get_all_process() -> Vec<ProcessEntry> {
+  get_all_process_pid()
+    .into_iter()
+    .filter_map(|pid| {
+      let mut entry = get_process_by_pid(pid)?;
+      // fallback to process name if we can't resolve the full path, like the Windows backend does
+      if entry.path.is_empty() {
+        entry.path = read_stat(pid).map(|stat| stat.comm).unwrap_or_default();
+      }
+      Some(entry)
+    })
+    .collect()
+}
+
+/// Get all process (PID only)
+pub fn get_all_process_pid() -> Vec<u32> {
+  let mut processes: Vec<u32> = Vec::with_capacity(512);
+  let Ok(entries) = fs::read_dir("/proc") else {
+    return processes;
+  };
+  for entry in entries.flatten() {
+    // only numeric entries are processes
+    if let Some(pid) = entry
+      .file_name()
+      .to_str()
+      .and_then(|s| s.parse::<u32>().ok())
+    {
+      processes.push(pid);
+    }
+  }
+  processes
+}
+
+/// Get all process (PID and parent PID pairs)
+pub fn get_all_process_ppid() -> Vec<(u32, u32)> {
+  get_all_process_pid()
+    .into_iter()
+    .filter_map(|pid| Some((pid, read_stat(pid)?.ppid)))
+    .collect()
+}
+
+/// Get all process (PID and parsed stat pairs)
+pub fn get_all_process_stat() -> Vec<(u32, ProcStat)> {
+  get_all_process_pid()
+    .into_iter()
+    .filter_map(|pid| Some((pid, read_stat(pid)?)))
+    .collect()
+}
+
+/// Get the parent PID of a process, 0 if the process no longer exists
+pub fn get_parent_pid(pid: u32) -> u32 {
+  read_stat(pid).map(|stat| stat.ppid).unwrap_or(0)
+}
+
+/// Get full path of a process using a given PID by resolving `/proc/<pid>/exe`.
+///
+/// Returns an empty string for kernel threads and processes owned by other users.
+pub fn get_process_full_path_by_pid(pid: u32) -> String {
+  match fs::read_link(format!("/proc/{pid}/exe")) {
+    Ok(path) => {
+      let path = path.to_string_lossy();
+      // the kernel appends this suffix if the executable has been replaced or removed
+      path.strip_suffix(" (deleted)").unwrap_or(&path).to_string()
+    }
+    Err(_) => String::new(),
+  }
+}
+
+/// Get a single process snapshot using a given PID, `None` if the process no longer exists
+pub fn get_process_by_pid(pid: u32) -> Option<ProcessEntry> {
+  let stat = read_stat(pid)?;
+  let (boot_time, ticks_per_second) = clock();
+  Some(ProcessEntry {
+    pid,
+    ppid: stat.ppid,
+    path: get_process_full_path_by_pid(pid),
+    start_time: ticks_to_unix_millis(stat.start_ticks, boot_time, ticks_per_second),
+  })
+}
+
+/// Get the command line arguments of a process, empty if the process is inaccessible
+pub fn read_cmdline(pid: u32) -> Vec<String> {
+  fs::read(format!("/proc/{pid}/cmdline"))
+    .map(|content| parse_nul_separated(&content))
+    .unwrap_or_default()
+}
+
+/// Get the environment variables of a process, empty if the process is inaccessible
+pub fn read_environ(pid: u32) -> HashMap<String, String> {
+  fs::read(format!("/proc/{pid}/environ"))
+    .map(|content| parse_environ(&content))
+    .unwrap_or_default()
+}
+
+/// Get the current working directory of a process
+pub fn read_cwd(pid: u32) -> Option<PathBuf> {
+  fs::read_link(format!("/proc/{pid}/cwd")).ok()
+}
+
+/// Get the start time of a process, `None` if the process no longer exists
+pub fn get_process_start_time(pid: u32) -> Option<i64> {
+  let (boot_time, ticks_per_second) = clock();
+  read_stat(pid)
+    .map(|stat| ticks_to_unix_millis(stat.start_ticks, boot_time, ticks_per_second))
+    .filter(|start_time| *start_time > 0)
+}
+
+/// Sample the resources a process consumed so far, `None` if the process no longer exists.
+///
+/// I/O counters require the permission to trace the process, they are `None` otherwise.
+pub fn get_process_resources(pid: u32) -> Option<ProcessResources> {
+  let stat = read_stat(pid)?;
+  let io = fs::read_to_string(format!("/proc/{pid}/io"))
+    .ok()
+    .and_then(|content| parse_io(&content));
+  let (_, ticks_per_second) = clock();
+  Some(ProcessResources {
+    peak_memory: fs::read_to_string(format!("/proc/{pid}/status"))
+      .ok()
+      .and_then(|content| parse_peak_resident_size(&content)),
+    cpu_cycles: None,
+    cpu_time: Some((stat.user_ticks + stat.system_ticks) * 1000 / ticks_per_second),
+    read_operations: io.as_ref().map(|io| io.read_operations),
+    write_operations: io.as_ref().map(|io| io.write_operations),
+    read_bytes: io.as_ref().map(|io| io.read_bytes),
+    write_bytes: io.as_ref().map(|io| io.write_bytes),
+    hard_faults: Some(stat.major_faults),
+  })
+}
+
+/// Get the command line, working directory and start time of a process
+pub fn get_process_details(pid: u32) -> ProcessDetails {
+  let args = read_cmdline(pid);
+  ProcessDetails {
+    cmdline: (!args.is_empty()).then(|| join_cmdline(&args)),
+    cwd: read_cwd(pid).map(|cwd| cwd.to_string_lossy().into_owned()),
+    start_time: get_process_start_time(pid),
+  }
+}
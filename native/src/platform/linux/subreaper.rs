@@ -0,0 +1,128 @@
+use std::{
+  collections::{HashMap, HashSet},
+  io,
+  sync::Mutex,
+};
+
+use super::procfs;
+
+/// The descendants of a launched process, tracked through their parents. It is the fallback of
+/// `Cgroup` where no cgroup can be created, e.g. in a Flatpak sandbox.
+///
+/// Both current process and the launched process are child subreapers, so that an orphaned
+/// descendant is reparented to the launched process while it runs, and to current process after
+/// it exits, instead of init. A process is a member if its parent is a member when it is seen,
+/// and stays a member wherever it is reparented to. Orphans reparented to current process are
+/// reaped by it.
+pub struct ProcessTree {
+  state: Mutex<TreeState>,
+}
+
+#[derive(Default)]
+struct TreeState {
+  /// Pid of the launched process, 0 until it is attached
+  root: u32,
+  /// Start time in clock ticks of the members by their pids
+  members: HashMap<u32, u64>,
+  /// Children of current process seen in the previous scan, with their start times
+  children: HashSet<(u32, u64)>,
+}
+
+impl ProcessTree {
+  /// Make current process a child subreaper and create an empty tree
+  pub fn new() -> io::Result<Self> {
+    set_child_subreaper()?;
+    Ok(Self {
+      state: Mutex::new(TreeState::default()),
+    })
+  }
+
+  /// Track the launched process and its descendants
+  pub fn attach(&self, pid: u32) -> io::Result<()> {
+    let start_ticks = procfs::read_stat(pid)
+      .map(|stat| stat.start_ticks)
+      .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("process {pid} exited")))?;
+    let mut state = self.state.lock().unwrap();
+    state.root = pid;
+    state.members.insert(pid, start_ticks);
+    Ok(())
+  }
+
+  /// Pids of the running members, the tree is refreshed with the running processes first
+  pub fn pids(&self) -> Vec<u32> {
+    let own_pid = std::process::id();
+    let own_path = procfs::get_process_full_path_by_pid(own_pid);
+    let mut processes = procfs::get_all_process_stat();
+    // parents start before their children, so that a whole new branch joins in a single scan
+    processes.sort_by_key(|(_, stat)| stat.start_ticks);
+
+    let mut state = self.state.lock().unwrap();
+    let state = &mut *state;
+    // a pid which is reused by another process is not a member any more
+    let running: HashMap<u32, u64> = processes
+      .iter()
+      .map(|(pid, stat)| (*pid, stat.start_ticks))
+      .collect();
+    state
+      .members
+      .retain(|pid, start_ticks| running.get(pid) == Some(start_ticks));
+    let is_root_running = state.members.contains_key(&state.root);
+    for (pid, stat) in &processes {
+      if state.members.contains_key(pid) {
+        continue;
+      }
+      let is_descendant = state
+        .members
+        .get(&stat.ppid)
+        .is_some_and(|parent_ticks| *parent_ticks <= stat.start_ticks);
+      // the launched process holds its orphans while it runs, a new child of current process is
+      // an orphan which has been reparented after it exited, unless current process spawned it
+      let is_orphan = !is_root_running
+        && stat.ppid == own_pid
+        && !state.children.contains(&(*pid, stat.start_ticks))
+        && procfs::get_process_full_path_by_pid(*pid) != own_path;
+      if is_descendant || is_orphan {
+        state.members.insert(*pid, stat.start_ticks);
+      }
+    }
+    state.children = processes
+      .iter()
+      .filter(|(_, stat)| stat.ppid == own_pid)
+      .map(|(pid, stat)| (*pid, stat.start_ticks))
+      .collect();
+
+    let mut pids = Vec::new();
+    for (pid, stat) in &processes {
+      if !state.members.contains_key(pid) {
+        continue;
+      }
+      if stat.state != 'Z' {
+        pids.push(*pid);
+      } else if stat.ppid == own_pid && *pid != state.root {
+        // nobody else waits for an orphan, the launched process is reaped by its waiter
+        reap(*pid);
+        state.members.remove(pid);
+      }
+    }
+    pids
+  }
+}
+
+/// Make the process a child subreaper, so that its orphaned descendants are reparented to it.
+///
+/// Only async-signal-safe functions are called, so it can be called between fork and exec. The
+/// attribute is kept across exec.
+pub fn set_child_subreaper() -> io::Result<()> {
+  if unsafe { libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) } < 0 {
+    return Err(io::Error::last_os_error());
+  }
+  Ok(())
+}
+
+/// Reap an exited child without blocking
+fn reap(pid: u32) {
+  let mut status = 0;
+  unsafe {
+    libc::waitpid(pid as libc::pid_t, &mut status, libc::WNOHANG);
+  }
+}
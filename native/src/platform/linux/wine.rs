@@ -0,0 +1,193 @@
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Component, Path, PathBuf},
+};
+
+use crate::platform::{linux::procfs, ProcessImage};
+
+/// Executable names of the Wine loader, Proton ships the same binaries
+const WINE_LOADERS: [&str; 4] = ["wine-preloader", "wine64-preloader", "wine", "wine64"];
+
+/// A Wine drive letter and the host directory its `dosdevices` symlink points to
+pub type DriveMapping = (char, PathBuf);
+
+/// Check if an executable path is the Wine loader rather than the actual program
+pub fn is_wine_loader(exe_path: &str) -> bool {
+  Path::new(exe_path)
+    .file_name()
+    .and_then(|name| name.to_str())
+    .is_some_and(|name| WINE_LOADERS.contains(&name))
+}
+
+/// Get the Wine prefix of a process from its environment, defaults to `~/.wine`
+pub fn get_wine_prefix(environ: &HashMap<String, String>) -> Option<PathBuf> {
+  if let Some(prefix) = environ.get("WINEPREFIX").filter(|v| !v.is_empty()) {
+    return Some(PathBuf::from(prefix));
+  }
+  environ
+    .get("HOME")
+    .map(|home| Path::new(home).join(".wine"))
+}
+
+/// Read all drive mappings of a Wine prefix from `<prefix>/dosdevices`
+pub fn read_drive_mappings(prefix: &Path) -> Vec<DriveMapping> {
+  let dosdevices = prefix.join("dosdevices");
+  let Ok(entries) = fs::read_dir(&dosdevices) else {
+    return Vec::new();
+  };
+  let mut drives = Vec::new();
+  for entry in entries.flatten() {
+    let name = entry.file_name();
+    let Some(letter) = name.to_str().and_then(parse_drive_name) else {
+      continue;
+    };
+    let Ok(target) = fs::read_link(entry.path()) else {
+      continue;
+    };
+    // links are usually relative to the dosdevices directory, e.g. `c: -> ../drive_c`
+    let target = dosdevices.join(target);
+    let target = fs::canonicalize(&target).unwrap_or_else(|_| lexical_normalize(&target));
+    drives.push((letter, target));
+  }
+  drives
+}
+
+/// Parse a dosdevices entry name such as `c:`, device entries such as `c::` are ignored
+fn parse_drive_name(name: &str) -> Option<char> {
+  let mut chars = name.chars();
+  let letter = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+  match (chars.next(), chars.next()) {
+    (Some(':'), None) => Some(letter.to_ascii_lowercase()),
+    _ => None,
+  }
+}
+
+/// Resolve `.` and `..` components without touching the file system
+fn lexical_normalize(path: &Path) -> PathBuf {
+  let mut normalized = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        normalized.pop();
+      }
+      c => normalized.push(c),
+    }
+  }
+  normalized
+}
+
+/// Split an absolute Windows path into its drive letter and the remaining components
+fn split_windows_path(path: &str) -> Option<(char, Vec<&str>)> {
+  // strip the `\\?\` long path prefix
+  let path = path.strip_prefix(r"\\?\").unwrap_or(path);
+  let mut chars = path.chars();
+  let letter = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+  if chars.next() != Some(':') {
+    return None;
+  }
+  let rest = chars.as_str();
+  if !rest.starts_with(['\\', '/']) {
+    return None;
+  }
+  let components = rest.split(['\\', '/']).filter(|c| !c.is_empty()).collect();
+  Some((letter.to_ascii_lowercase(), components))
+}
+
+/// Check if a string is an absolute path on a DOS drive (`X:\...`), which Wine can translate
+/// through its drive mappings
+pub fn is_dos_drive_path(path: &str) -> bool {
+  split_windows_path(path).is_some()
+}
+
+/// Translate a Windows path such as `C:\Games\game.exe` to a host path using drive mappings
+pub fn windows_to_host_path(path: &str, drives: &[DriveMapping]) -> Option<PathBuf> {
+  let (letter, components) = split_windows_path(path)?;
+  let (_, root) = drives.iter().find(|(l, _)| *l == letter)?;
+  let mut host = root.clone();
+  for component in components {
+    host.push(component);
+  }
+  Some(lexical_normalize(&host))
+}
+
+/// Translate a host path to a Windows path using the most specific drive mapping.
+///
+/// `Z:` usually maps to `/`, so it is only used if no other drive contains the path.
+pub fn host_to_windows_path(path: &Path, drives: &[DriveMapping]) -> Option<String> {
+  let (letter, rest) = drives
+    .iter()
+    .filter_map(|(letter, root)| {
+      path
+        .strip_prefix(root)
+        .ok()
+        .map(|rest| (letter, root, rest))
+    })
+    .max_by_key(|(_, root, _)| root.components().count())
+    .map(|(letter, _, rest)| (letter, rest))?;
+  let components: Vec<_> = rest
+    .components()
+    .filter_map(|c| c.as_os_str().to_str())
+    .collect();
+  Some(format!(
+    "{}:\\{}",
+    letter.to_ascii_uppercase(),
+    components.join("\\")
+  ))
+}
+
+/// Find the Windows program a Wine loader is running from its command line.
+///
+/// Once started, Wine rewrites the command line to the Windows path of the program, but at exec
+/// time it still contains the loader invocation, e.g. `wine64-preloader wine64 game.exe`.
+///
+/// The program is the first argument after the loaders, the arguments following it belong to the
+/// program, e.g. a save file.
+pub fn find_program_in_cmdline(cmdline: &[String]) -> Option<&str> {
+  let program = cmdline.iter().find(|arg| !is_wine_loader(arg))?;
+  (is_dos_drive_path(program) || program.to_ascii_lowercase().ends_with(".exe"))
+    .then_some(program.as_str())
+}
+
+/// Resolve the program a Wine process is running to both its host path and Windows path
+pub fn resolve_program(
+  program: &str,
+  cwd: Option<&Path>,
+  drives: &[DriveMapping],
+) -> Option<ProcessImage> {
+  if is_dos_drive_path(program) {
+    let host = windows_to_host_path(program, drives)?;
+    return Some(ProcessImage {
+      path: host.to_string_lossy().into_owned(),
+      aliases: vec![program.to_string()],
+    });
+  }
+  // a host path, either absolute or relative to the working directory
+  let program = program.replace('\\', "/");
+  let host = match cwd {
+    Some(cwd) => lexical_normalize(&cwd.join(&program)),
+    None if program.starts_with('/') => lexical_normalize(Path::new(&program)),
+    None => return None,
+  };
+  let aliases = host_to_windows_path(&host, drives).into_iter().collect();
+  Some(ProcessImage {
+    path: host.to_string_lossy().into_owned(),
+    aliases,
+  })
+}
+
+/// Resolve the Windows program hosted by a Wine loader process, `None` if it is not a Wine process
+pub fn resolve_process_image(pid: u32, exe_path: &str) -> Option<ProcessImage> {
+  if !is_wine_loader(exe_path) {
+    return None;
+  }
+  let cmdline = procfs::read_cmdline(pid);
+  let program = find_program_in_cmdline(&cmdline)?;
+  let environ = procfs::read_environ(pid);
+  let drives = get_wine_prefix(&environ)
+    .map(|prefix| read_drive_mappings(&prefix))
+    .unwrap_or_default();
+  let cwd = procfs::read_cwd(pid);
+  resolve_program(program, cwd.as_deref(), &drives)
+}
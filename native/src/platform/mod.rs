@@ -0,0 +1,304 @@
+use std::fmt;
+use tokio::{process::Command, sync::mpsc};
+
+use crate::log;
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(not(any(windows, target_os = "linux")))]
+mod unsupported;
+#[cfg(windows)]
+mod win;
+
+/// The platform implementation selected at compile time
+#[cfg(target_os = "linux")]
+type Current = linux::LinuxPlatform;
+#[cfg(not(any(windows, target_os = "linux")))]
+type Current = unsupported::UnsupportedPlatform;
+#[cfg(windows)]
+type Current = win::WinPlatform;
+
+#[derive(Debug)]
+pub enum PlatformError {
+  /// The capability has no implementation on current platform
+  Unsupported(&'static str),
+  /// An underlying OS API failed
+  Os(String),
+}
+
+impl fmt::Display for PlatformError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PlatformError::Unsupported(capability) => write!(
+        f,
+        "{} is not supported on {}",
+        capability,
+        std::env::consts::OS
+      ),
+      PlatformError::Os(msg) => write!(f, "{}", msg),
+    }
+  }
+}
+
+impl std::error::Error for PlatformError {}
+
+impl From<PlatformError> for napi::Error {
+  fn from(err: PlatformError) -> Self {
+    napi::Error::new(napi::Status::GenericFailure, err.to_string())
+  }
+}
+
+#[cfg(windows)]
+impl From<windows_core::Error> for PlatformError {
+  fn from(err: windows_core::Error) -> Self {
+    PlatformError::Os(format!("{} (0x{:X})", err.message(), err.code().0))
+  }
+}
+
+pub type Result<T> = std::result::Result<T, PlatformError>;
+
+/// A platform neutral snapshot of a single process
+#[derive(Debug, Clone, Default)]
+pub struct ProcessEntry {
+  pub pid: u32,
+  /// Parent process PID, 0 if it can not be retrieved
+  pub ppid: u32,
+  /// Resolved full path of the process image, empty if the process is inaccessible
+  pub path: String,
+  /// Process start time in milliseconds since UNIX epoch, 0 if it can not be retrieved
+  pub start_time: i64,
+}
+
+/// What a process was launched with, fields are `None` if they can not be retrieved
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessDetails {
+  /// Full command line including the program
+  pub cmdline: Option<String>,
+  /// Working directory
+  pub cwd: Option<String>,
+  /// Start time in milliseconds since UNIX epoch
+  pub start_time: Option<i64>,
+}
+
+/// Resources a process consumed over its lifetime, fields are `None` if they can not be retrieved
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProcessResources {
+  /// Peak memory in bytes, the peak commit charge on Windows and the peak resident set on Linux
+  pub peak_memory: Option<u64>,
+  /// CPU cycles spent, only reported on Windows
+  pub cpu_cycles: Option<u64>,
+  /// CPU time in milliseconds spent in user and kernel mode, only reported on Linux
+  pub cpu_time: Option<u64>,
+  pub read_operations: Option<u64>,
+  pub write_operations: Option<u64>,
+  pub read_bytes: Option<u64>,
+  pub write_bytes: Option<u64>,
+  /// Page faults which had to read from disk
+  pub hard_faults: Option<u64>,
+}
+
+impl ProcessResources {
+  /// Update with a later sample of the same process, counters are replaced while the peak is kept
+  pub fn update(&mut self, sample: &ProcessResources) {
+    let peak_memory = self.peak_memory.max(sample.peak_memory);
+    *self = ProcessResources {
+      peak_memory,
+      cpu_cycles: sample.cpu_cycles.or(self.cpu_cycles),
+      cpu_time: sample.cpu_time.or(self.cpu_time),
+      read_operations: sample.read_operations.or(self.read_operations),
+      write_operations: sample.write_operations.or(self.write_operations),
+      read_bytes: sample.read_bytes.or(self.read_bytes),
+      write_bytes: sample.write_bytes.or(self.write_bytes),
+      hard_faults: sample.hard_faults.or(self.hard_faults),
+    };
+  }
+
+  /// Add the resources of another process, counters are summed and the highest peak is kept
+  pub fn accumulate(&mut self, other: &ProcessResources) {
+    let sum = |a: Option<u64>, b: Option<u64>| match (a, b) {
+      (Some(a), Some(b)) => Some(a.saturating_add(b)),
+      (a, b) => a.or(b),
+    };
+    *self = ProcessResources {
+      peak_memory: self.peak_memory.max(other.peak_memory),
+      cpu_cycles: sum(self.cpu_cycles, other.cpu_cycles),
+      cpu_time: sum(self.cpu_time, other.cpu_time),
+      read_operations: sum(self.read_operations, other.read_operations),
+      write_operations: sum(self.write_operations, other.write_operations),
+      read_bytes: sum(self.read_bytes, other.read_bytes),
+      write_bytes: sum(self.write_bytes, other.write_bytes),
+      hard_faults: sum(self.hard_faults, other.hard_faults),
+    };
+  }
+}
+
+/// Paths a process can be attributed to
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessImage {
+  /// Full path of the program the process is running
+  pub path: String,
+  /// Alternative paths of the same program, e.g. the Windows path of a program running in Wine
+  pub aliases: Vec<String>,
+}
+
+/// A handle of a process which keeps its exit code readable after it exited
+pub trait ProcessExitHandle: Send + Sync {
+  /// Exit code of the process, `None` while it is running or if it can not be retrieved
+  fn exit_code(&self) -> Option<i64>;
+}
+
+/// A group which contains a spawned process and all its descendants, e.g. a Job Object, so that
+/// processes which escape their parents still belong to it
+pub trait ProcessGroup: Send + Sync {
+  /// Put the process spawned with the prepared command in the group. The process is resumed even if
+  /// it can not be contained, in case it has been created suspended.
+  fn attach(&self, pid: u32) -> Result<()>;
+  /// Pids of the running processes in the group
+  fn pids(&self) -> Vec<u32>;
+}
+
+/// Content of a system notification, empty fields are omitted
+#[derive(Debug, Default)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub struct Notification {
+  pub app_id: String,
+  pub title: Option<String>,
+  pub line1: Option<String>,
+  pub line2: Option<String>,
+  pub image: Option<String>,
+  pub silent: Option<bool>,
+}
+
+/// Process listing
+pub trait ProcessBackend {
+  fn get_all_process() -> Vec<ProcessEntry>;
+  fn get_all_process_ppid() -> Vec<(u32, u32)>;
+  fn get_process_full_path_by_pid(pid: u32) -> String;
+
+  /// Get the command line, working directory and start time of a process
+  fn get_process_details(_pid: u32) -> ProcessDetails {
+    ProcessDetails::default()
+  }
+
+  /// Get the start time of a process in milliseconds since UNIX epoch, it tells a process apart
+  /// from a later one which reuses its pid
+  fn get_process_start_time(_pid: u32) -> Option<i64> {
+    None
+  }
+
+  /// Sample the resources a running process consumed so far
+  fn get_process_resources(_pid: u32) -> Option<ProcessResources> {
+    None
+  }
+
+  /// Open a handle to read the exit code of a process later, for monitors whose termination events
+  /// do not carry it
+  fn open_process_exit_handle(_pid: u32) -> Option<Box<dyn ProcessExitHandle>> {
+    None
+  }
+
+  /// Resolve the program a process is actually running, given its image path.
+  ///
+  /// The image path is the program itself unless it is hosted by a loader or an interpreter.
+  fn resolve_process_image(_pid: u32, path: String) -> ProcessImage {
+    ProcessImage {
+      path,
+      aliases: Vec::new(),
+    }
+  }
+
+  /// Prepare a command so that the process it spawns and all its descendants are contained in a new
+  /// group, the process has to be attached to the group once it is spawned
+  fn prepare_process_group(_command: &mut Command, _name: &str) -> Result<Box<dyn ProcessGroup>> {
+    Err(PlatformError::Unsupported("process group"))
+  }
+}
+
+/// Privilege checks
+pub trait PrivilegeBackend {
+  /// Whether current process has the privilege to use real-time process monitors
+  fn is_elevated_privilege() -> Result<bool>;
+}
+
+/// System notifications
+pub trait NotificationBackend {
+  fn send_notification(notification: Notification) -> Result<()>;
+}
+
+/// Foreground window tracking
+pub trait ForegroundBackend {
+  /// Install a hook which sends the PID of the new foreground window through `tx`
+  fn install_foreground_hook(tx: mpsc::Sender<u32>) -> Result<()>;
+  fn uninstall_foreground_hook();
+}
+
+/// Get all process information (pid, ppid, full path, start time)
+pub fn get_all_process() -> Vec<ProcessEntry> {
+  Current::get_all_process()
+}
+
+/// Get all process (PID and parent PID pairs)
+pub fn get_all_process_ppid() -> Vec<(u32, u32)> {
+  Current::get_all_process_ppid()
+}
+
+/// Get full path of a process using a given PID, empty if the process is inaccessible
+pub fn get_process_full_path_by_pid(pid: u32) -> String {
+  Current::get_process_full_path_by_pid(pid)
+}
+
+/// Get the command line, working directory and start time of a process
+pub fn get_process_details(pid: u32) -> ProcessDetails {
+  Current::get_process_details(pid)
+}
+
+/// Get the start time of a process, `None` if the process is inaccessible or has exited
+pub fn get_process_start_time(pid: u32) -> Option<i64> {
+  Current::get_process_start_time(pid)
+}
+
+/// Sample the resources a running process consumed so far, `None` if it is not supported or the
+/// process is inaccessible
+pub fn get_process_resources(pid: u32) -> Option<ProcessResources> {
+  Current::get_process_resources(pid)
+}
+
+/// Open a handle to read the exit code of a process later, `None` if it is not supported or the
+/// process is inaccessible
+pub fn open_process_exit_handle(pid: u32) -> Option<Box<dyn ProcessExitHandle>> {
+  Current::open_process_exit_handle(pid)
+}
+
+/// Resolve the program a process is actually running, given its image path
+pub fn resolve_process_image(pid: u32, path: String) -> ProcessImage {
+  Current::resolve_process_image(pid, path)
+}
+
+/// Prepare a command so that the process it spawns and all its descendants are contained in a new
+/// group named after `name`, see `ProcessGroup::attach`
+pub fn prepare_process_group(command: &mut Command, name: &str) -> Result<Box<dyn ProcessGroup>> {
+  Current::prepare_process_group(command, name)
+}
+
+/// Check if current process is running with elevated privilege, errors are treated as `false`
+pub fn is_elevated_privilege() -> bool {
+  match Current::is_elevated_privilege() {
+    Err(e) => {
+      log::error(format!("failed to check privilege: {}", e).as_str());
+      false
+    }
+    Ok(v) => v,
+  }
+}
+
+pub fn send_notification(notification: Notification) -> Result<()> {
+  Current::send_notification(notification)
+}
+
+pub fn install_foreground_hook(tx: mpsc::Sender<u32>) -> Result<()> {
+  Current::install_foreground_hook(tx)
+}
+
+pub fn uninstall_foreground_hook() {
+  Current::uninstall_foreground_hook()
+}
@@ -0,0 +1,43 @@
+use tokio::sync::mpsc;
+
+use crate::platform::{
+  ForegroundBackend, Notification, NotificationBackend, PlatformError, PrivilegeBackend,
+  ProcessBackend, ProcessEntry, Result,
+};
+
+/// Fallback for platforms without a native implementation
+pub struct UnsupportedPlatform;
+
+impl ProcessBackend for UnsupportedPlatform {
+  fn get_all_process() -> Vec<ProcessEntry> {
+    Vec::new()
+  }
+
+  fn get_all_process_ppid() -> Vec<(u32, u32)> {
+    Vec::new()
+  }
+
+  fn get_process_full_path_by_pid(_pid: u32) -> String {
+    String::new()
+  }
+}
+
+impl PrivilegeBackend for UnsupportedPlatform {
+  fn is_elevated_privilege() -> Result<bool> {
+    Err(PlatformError::Unsupported("privilege check"))
+  }
+}
+
+impl NotificationBackend for UnsupportedPlatform {
+  fn send_notification(_notification: Notification) -> Result<()> {
+    Err(PlatformError::Unsupported("system notification"))
+  }
+}
+
+impl ForegroundBackend for UnsupportedPlatform {
+  fn install_foreground_hook(_tx: mpsc::Sender<u32>) -> Result<()> {
+    Err(PlatformError::Unsupported("foreground hook"))
+  }
+
+  fn uninstall_foreground_hook() {}
+}
@@ -0,0 +1,84 @@
+use tokio::{process::Command, sync::mpsc};
+
+use crate::platform::{
+  ForegroundBackend, Notification, NotificationBackend, PlatformError, PrivilegeBackend,
+  ProcessBackend, ProcessDetails, ProcessEntry, ProcessExitHandle, ProcessGroup, Result,
+};
+use crate::win32;
+
+/// Windows implementation backed by Win32 and WinRT APIs
+pub struct WinPlatform;
+
+impl ProcessBackend for WinPlatform {
+  fn get_all_process() -> Vec<ProcessEntry> {
+    win32::get_all_process()
+  }
+
+  fn get_all_process_ppid() -> Vec<(u32, u32)> {
+    win32::get_all_process_ppid()
+  }
+
+  fn get_process_full_path_by_pid(pid: u32) -> String {
+    win32::get_process_full_path_by_pid(pid)
+  }
+
+  fn get_process_details(pid: u32) -> ProcessDetails {
+    win32::get_process_details(pid)
+  }
+
+  fn get_process_start_time(pid: u32) -> Option<i64> {
+    win32::get_process_start_time_by_pid(pid)
+  }
+
+  fn open_process_exit_handle(pid: u32) -> Option<Box<dyn ProcessExitHandle>> {
+    let handle = win32::ProcessHandle::open(pid)?;
+    Some(Box::new(handle))
+  }
+
+  fn prepare_process_group(command: &mut Command, _name: &str) -> Result<Box<dyn ProcessGroup>> {
+    let job = win32::JobObject::new()?;
+    // keep the process from creating any process before it is in the job
+    command.creation_flags(win32::CREATE_SUSPENDED);
+    Ok(Box::new(job))
+  }
+}
+
+impl ProcessGroup for win32::JobObject {
+  fn attach(&self, pid: u32) -> Result<()> {
+    let assigned = self.assign(pid);
+    win32::resume_process(pid)?;
+    Ok(assigned?)
+  }
+
+  fn pids(&self) -> Vec<u32> {
+    win32::JobObject::pids(self)
+  }
+}
+
+impl ProcessExitHandle for win32::ProcessHandle {
+  fn exit_code(&self) -> Option<i64> {
+    win32::ProcessHandle::exit_code(self)
+  }
+}
+
+impl PrivilegeBackend for WinPlatform {
+  fn is_elevated_privilege() -> Result<bool> {
+    Ok(win32::is_elevated_privilege()?)
+  }
+}
+
+impl NotificationBackend for WinPlatform {
+  fn send_notification(notification: Notification) -> Result<()> {
+    Ok(win32::send_notification(notification)?)
+  }
+}
+
+impl ForegroundBackend for WinPlatform {
+  fn install_foreground_hook(tx: mpsc::Sender<u32>) -> Result<()> {
+    win32::install_foreground_hook(tx).map_err(PlatformError::Os)
+  }
+
+  fn uninstall_foreground_hook() {
+    win32::uninstall_foreground_hook()
+  }
+}
@@ -1,2 +1,33 @@
+mod test_encoding;
+mod test_etw_event;
+mod test_gm;
+mod test_known_games;
+#[cfg(target_os = "linux")]
+mod test_launcher;
 mod test_monitor;
+#[cfg(target_os = "linux")]
+mod test_netlink;
+mod test_nt_path;
+mod test_path;
+#[cfg(target_os = "linux")]
+mod test_platform;
+#[cfg(target_os = "linux")]
+mod test_procfs;
+mod test_rules;
+mod test_steam;
+#[cfg(windows)]
 mod test_win32;
+#[cfg(target_os = "linux")]
+mod test_wine;
+
+use crate::napi_monitor::KnownGame;
+
+/// A known game with a single path in its default match mode
+fn known_game(id: &str, path: &str) -> KnownGame {
+  KnownGame {
+    id: id.to_string(),
+    paths: vec![path.to_string()],
+    match_mode: None,
+    enabled: None,
+  }
+}
@@ -0,0 +1,62 @@
+use crate::utils::encoding;
+
+const CP_SHIFT_JIS: u16 = 932;
+const CP_UTF8: u16 = 65001;
+
+#[test]
+fn test_decode_shift_jis() {
+  // `ゲーム\game.exe`
+  let bytes = [
+    0x83, 0x51, 0x81, 0x5b, 0x83, 0x80, b'\\', b'g', b'a', b'm', b'e', b'.', b'e', b'x', b'e',
+  ];
+  assert_eq!(
+    encoding::decode_ansi(&bytes, CP_SHIFT_JIS),
+    r"ゲーム\game.exe"
+  );
+  // the trail byte of `表` is 0x5c, which must not be taken as a path separator
+  assert_eq!(
+    encoding::decode_ansi(&[0x95, 0x5c, 0x8e, 0xa6, b'\\', b'a'], CP_SHIFT_JIS),
+    r"表示\a"
+  );
+  // a truncated character is replaced instead of failing
+  assert_eq!(
+    encoding::decode_ansi(&[b'a', 0x83], CP_SHIFT_JIS),
+    "a\u{fffd}"
+  );
+}
+
+#[test]
+fn test_decode_other_code_pages() {
+  assert_eq!(encoding::decode_ansi(b"Caf\xe9", 1252), "Café");
+  assert_eq!(
+    encoding::decode_ansi(&[0xc8, 0xe3, 0xf0, 0xe0], 1251),
+    "Игра"
+  );
+  assert_eq!(
+    encoding::decode_ansi("ゲーム".as_bytes(), CP_UTF8),
+    "ゲーム"
+  );
+  // unknown code pages keep valid UTF-8 and fall back to Windows-1252 otherwise
+  assert_eq!(encoding::decode_ansi("ゲーム".as_bytes(), 0), "ゲーム");
+  assert_eq!(encoding::decode_ansi(b"Caf\xe9", 0), "Café");
+  // the string ends at its terminator
+  assert_eq!(
+    encoding::decode_ansi(b"game.exe\0junk", CP_SHIFT_JIS),
+    "game.exe"
+  );
+}
+
+#[test]
+fn test_decode_utf16le() {
+  let bytes: Vec<u8> = r"C:\ゲーム\game.exe"
+    .encode_utf16()
+    .chain([0, 0x41])
+    .flat_map(u16::to_le_bytes)
+    .collect();
+  assert_eq!(encoding::decode_utf16le(&bytes), r"C:\ゲーム\game.exe");
+  // an unpaired surrogate is replaced
+  assert_eq!(
+    encoding::decode_utf16le(&[0x3d, 0xd8, 0x41, 0x00]),
+    "\u{fffd}A"
+  );
+}
@@ -0,0 +1,217 @@
+use crate::utils::etw_event::{self, DecodeError, Elevation};
+
+const IMAGE_NAME: &str = r"\Device\HarddiskVolume3\Games\ゲーム\game.exe";
+const CREATE_TIME: u64 = 133_500_000_000_000_000;
+/// ANSI code page of Japanese Windows
+const CP_SHIFT_JIS: u16 = 932;
+
+/// Builds event user data field by field, the way the kernel lays it out
+#[derive(Default)]
+struct Fixture(Vec<u8>);
+
+impl Fixture {
+  fn u32(mut self, value: u32) -> Self {
+    self.0.extend(value.to_le_bytes());
+    self
+  }
+
+  fn u64(mut self, value: u64) -> Self {
+    self.0.extend(value.to_le_bytes());
+    self
+  }
+
+  fn unicode(mut self, value: &str) -> Self {
+    self
+      .0
+      .extend(value.encode_utf16().chain([0]).flat_map(u16::to_le_bytes));
+    self
+  }
+
+  fn ansi(mut self, value: &[u8]) -> Self {
+    self.0.extend(value.iter().chain(&[0]));
+    self
+  }
+
+  /// `S-1-16-12288`, the high mandatory level
+  fn sid(mut self) -> Self {
+    self.0.extend([1, 1, 0, 0, 0, 0, 0, 16]);
+    self.0.extend(12288u32.to_le_bytes());
+    self
+  }
+}
+
+/// Start event data of the given version, for process 1234 started by 1000 in session 1
+fn process_start(version: u8) -> Vec<u8> {
+  let mut fixture = Fixture::default().u32(1234);
+  if version >= 2 {
+    fixture = fixture.u64(77);
+  }
+  fixture = fixture.u64(CREATE_TIME).u32(1000);
+  if version >= 2 {
+    fixture = fixture.u64(66);
+  }
+  fixture = fixture.u32(1);
+  if version >= 1 {
+    fixture = fixture.u32(0);
+  }
+  if version >= 3 {
+    fixture = fixture.u32(2).u32(1).sid();
+  }
+  fixture = fixture.unicode(IMAGE_NAME);
+  if version >= 1 {
+    fixture = fixture
+      .u32(0)
+      .u32(0x5f00_0000)
+      .unicode("Vendor.Game_1.0.0.0_x64__8wekyb3d8bbwe")
+      .unicode("App");
+  }
+  if version >= 4 {
+    fixture = fixture.u32(0x10);
+  }
+  fixture.0
+}
+
+/// Stop event data of the given version, for process 1234 exiting with code 3
+fn process_stop(version: u8) -> Vec<u8> {
+  process_stop_with_image(version, b"game.exe")
+}
+
+fn process_stop_with_image(version: u8, image_name: &[u8]) -> Vec<u8> {
+  let mut fixture = Fixture::default().u32(1234);
+  if version >= 2 {
+    fixture = fixture.u64(77);
+  }
+  fixture = fixture
+    .u64(CREATE_TIME)
+    .u64(CREATE_TIME + 600_000_000)
+    .u32(3)
+    .u32(1)
+    .u32(250)
+    .u64(1 << 20)
+    .u64(512 << 20);
+  if version >= 1 {
+    fixture = fixture
+      .u64(9_000_000)
+      .u32(40)
+      .u32(20)
+      .u32(1024)
+      .u32(16)
+      .u32(5);
+  }
+  fixture.ansi(image_name).0
+}
+
+#[test]
+fn test_decode_process_start() {
+  for version in 0..=4 {
+    let event = etw_event::decode_process_start(version, &process_start(version)).unwrap();
+    assert_eq!(event.process_id, 1234, "version {version}");
+    assert_eq!(event.create_time, CREATE_TIME, "version {version}");
+    assert_eq!(event.parent_process_id, 1000, "version {version}");
+    assert_eq!(event.session_id, 1, "version {version}");
+    assert_eq!(event.image_name, IMAGE_NAME, "version {version}");
+    assert_eq!(event.sequence_number.is_some(), version >= 2);
+    assert_eq!(event.parent_sequence_number.is_some(), version >= 2);
+    assert_eq!(event.flags.is_some(), version >= 1);
+    assert_eq!(event.security_mitigations.is_some(), version >= 4);
+  }
+
+  let v4 = etw_event::decode_process_start(4, &process_start(4)).unwrap();
+  assert_eq!(v4.sequence_number, Some(77));
+  assert_eq!(v4.parent_sequence_number, Some(66));
+  assert_eq!(
+    v4.elevation,
+    Some(Elevation {
+      elevation_type: 2,
+      is_elevated: true
+    })
+  );
+  assert_eq!(
+    v4.package_full_name.as_deref(),
+    Some("Vendor.Game_1.0.0.0_x64__8wekyb3d8bbwe")
+  );
+  assert_eq!(v4.package_relative_app_id.as_deref(), Some("App"));
+  assert_eq!(v4.security_mitigations, Some(0x10));
+
+  let v0 = etw_event::decode_process_start(0, &process_start(0)).unwrap();
+  assert_eq!(v0.elevation, None);
+  assert_eq!(v0.package_full_name, None);
+}
+
+#[test]
+fn test_decode_newer_version() {
+  // later versions append fields, which are ignored
+  let mut data = process_start(4);
+  data.extend(42u32.to_le_bytes());
+  let event = etw_event::decode_process_start(5, &data).unwrap();
+  assert_eq!(event.image_name, IMAGE_NAME);
+  assert_eq!(event.security_mitigations, Some(0x10));
+}
+
+#[test]
+fn test_decode_process_stop() {
+  for version in 0..=2 {
+    let event =
+      etw_event::decode_process_stop(version, &process_stop(version), CP_SHIFT_JIS).unwrap();
+    assert_eq!(event.process_id, 1234, "version {version}");
+    assert_eq!(event.create_time, CREATE_TIME, "version {version}");
+    assert_eq!(event.exit_code, 3, "version {version}");
+    assert_eq!(event.handle_count, 250, "version {version}");
+    assert_eq!(event.commit_peak, 512 << 20, "version {version}");
+    assert_eq!(event.image_name, "game.exe", "version {version}");
+    assert_eq!(event.sequence_number.is_some(), version >= 2);
+    assert_eq!(event.cpu_cycle_count.is_some(), version >= 1);
+  }
+
+  let resources = etw_event::decode_process_stop(2, &process_stop(2), CP_SHIFT_JIS)
+    .unwrap()
+    .resources();
+  assert_eq!(resources.peak_memory, Some(512 << 20));
+  assert_eq!(resources.cpu_cycles, Some(9_000_000));
+  assert_eq!(resources.read_operations, Some(40));
+  assert_eq!(resources.read_bytes, Some(1024 * 1024));
+  assert_eq!(resources.write_bytes, Some(16 * 1024));
+  assert_eq!(resources.hard_faults, Some(5));
+
+  // V0 has no I/O counters
+  let resources = etw_event::decode_process_stop(0, &process_stop(0), CP_SHIFT_JIS)
+    .unwrap()
+    .resources();
+  assert_eq!(resources.peak_memory, Some(512 << 20));
+  assert_eq!(resources.read_bytes, None);
+}
+
+#[test]
+fn test_decode_ansi_image_name() {
+  // `ゲーム.exe` in Shift-JIS, the image name of a stopped process is in the ANSI code page
+  let image_name = [0x83, 0x51, 0x81, 0x5b, 0x83, 0x80, b'.', b'e', b'x', b'e'];
+  let data = process_stop_with_image(2, &image_name);
+  let event = etw_event::decode_process_stop(2, &data, CP_SHIFT_JIS).unwrap();
+  assert_eq!(event.image_name, "ゲーム.exe");
+  // systems using UTF-8 as their ANSI code page
+  let data = process_stop_with_image(2, "ゲーム.exe".as_bytes());
+  let event = etw_event::decode_process_stop(2, &data, 65001).unwrap();
+  assert_eq!(event.image_name, "ゲーム.exe");
+}
+
+#[test]
+fn test_decode_truncated() {
+  let data = process_start(4);
+  assert_eq!(
+    etw_event::decode_process_start(4, &data[..30]),
+    Err(DecodeError {
+      field: "ParentProcessSequenceNumber",
+      version: 4
+    })
+  );
+  // the length of the mandatory label is read from its header
+  let error = etw_event::decode_process_start(4, &data[..52]).unwrap_err();
+  assert_eq!(error.field, "MandatoryLabel");
+  assert_eq!(
+    error.to_string(),
+    "event data (version 4) is truncated at MandatoryLabel"
+  );
+
+  assert!(etw_event::decode_process_stop(2, &process_stop(2)[..60], CP_SHIFT_JIS).is_err());
+  assert!(etw_event::decode_process_stop(0, &[], CP_SHIFT_JIS).is_err());
+}
@@ -0,0 +1,548 @@
+use std::{
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use crate::{
+  monitor::{
+    gm::{self, GameManager},
+    known_games::KnownGameMatch,
+    rules::CompiledRule,
+    ProcessIdentity, ProcessMessage,
+  },
+  napi_monitor::{
+    KnownGame, KnownGameMatchMode, KnownGamePatch, KnownGameReattribution, MatchingRule,
+    SessionSummary,
+  },
+  platform::{self, ProcessEntry, ProcessGroup, ProcessResources},
+  tests::known_game,
+  utils::path::NormalizedPath,
+};
+
+// far beyond `pid_max` of Linux, so that no real process is inspected while resolving images
+const LAUNCHER_PID: u32 = 5_000_001;
+const GAME_PID: u32 = 5_000_002;
+const HELPER_PID: u32 = 5_000_003;
+const UNRELATED_PID: u32 = 5_000_004;
+
+/// A process group whose processes are set by the test
+struct FakeGroup(Mutex<Vec<u32>>);
+
+impl ProcessGroup for FakeGroup {
+  fn attach(&self, _pid: u32) -> platform::Result<()> {
+    Ok(())
+  }
+
+  fn pids(&self) -> Vec<u32> {
+    self.0.lock().unwrap().clone()
+  }
+}
+
+#[test]
+fn test_process_lineage() {
+  let mut gm = GameManager::new();
+  gm.replace_known_games(vec![known_game("game-id", "/games/launcher")]);
+
+  gm.handle_process_message(ProcessMessage::started(
+    LAUNCHER_PID,
+    1,
+    "/games/launcher/launcher.exe".to_string(),
+  ));
+  // the launcher spawns the actual game from another directory, which spawns a helper
+  gm.handle_process_message(ProcessMessage::started(
+    GAME_PID,
+    LAUNCHER_PID,
+    "/games/data/game.exe".to_string(),
+  ));
+  gm.handle_process_message(ProcessMessage::started(
+    HELPER_PID,
+    GAME_PID,
+    "/games/data/helper.exe".to_string(),
+  ));
+  gm.handle_process_message(ProcessMessage::started(
+    UNRELATED_PID,
+    1,
+    "/games/data/unrelated.exe".to_string(),
+  ));
+  assert_eq!(
+    gm.get_running_game_id(GAME_PID),
+    Some("game-id".to_string())
+  );
+  assert_eq!(
+    gm.get_running_game_id(HELPER_PID),
+    Some("game-id".to_string())
+  );
+  assert_eq!(gm.get_running_game_id(UNRELATED_PID), None);
+
+  // the game keeps running after the launcher exits
+  gm.handle_process_message(ProcessMessage::terminated(LAUNCHER_PID, String::new()));
+  assert!(gm.is_running("/games/data".to_string(), Some(true)));
+  gm.handle_process_message(ProcessMessage::terminated(GAME_PID, String::new()));
+  gm.handle_process_message(ProcessMessage::terminated(HELPER_PID, String::new()));
+  assert!(!gm.is_running("/games/data".to_string(), Some(true)));
+  assert_eq!(gm.get_running_game_id(0), None);
+}
+
+#[test]
+fn test_pid_reuse() {
+  assert!(ProcessIdentity::new(GAME_PID, Some(1000)).matches(&ProcessIdentity::new(GAME_PID, None)));
+  assert!(!ProcessIdentity::new(GAME_PID, Some(1000))
+    .matches(&ProcessIdentity::new(GAME_PID, Some(2000))));
+  assert!(!ProcessIdentity::new(GAME_PID, None).matches(&ProcessIdentity::new(HELPER_PID, None)));
+
+  let mut gm = GameManager::new();
+  gm.replace_known_games(vec![known_game("game-id", "/games/reuse")]);
+  let started = |pid: u32, path: &str, start_time: i64| {
+    ProcessMessage::started(pid, 1, path.to_string()).with_start_time(start_time)
+  };
+  gm.handle_process_message(started(GAME_PID, "/games/reuse/game.exe", 1000));
+  // a late exit of an earlier process with the same pid does not end the game
+  gm.handle_process_message(
+    ProcessMessage::terminated(GAME_PID, String::new()).with_start_time(500),
+  );
+  assert!(gm.is_running("/games/reuse".to_string(), Some(true)));
+  // a duplicated start is ignored
+  gm.handle_process_message(started(GAME_PID, "/games/reuse/game.exe", 1000));
+  assert_eq!(
+    gm.get_session_summary("game-id").map(|s| s.pids.len()),
+    Some(1)
+  );
+
+  // the game exited unnoticed and an unrelated process took its pid
+  gm.handle_process_message(started(GAME_PID, "/usr/bin/unrelated", 2000));
+  assert!(!gm.is_running("/games/reuse".to_string(), Some(true)));
+  assert!(gm.get_session_summary("game-id").is_none());
+
+  // the game started again with a reused pid
+  gm.handle_process_message(started(GAME_PID, "/games/reuse/game.exe", 3000));
+  assert!(gm.is_running("/games/reuse".to_string(), Some(true)));
+  // an exit without a start time ends it, as it can not be told apart
+  gm.handle_process_message(ProcessMessage::terminated(GAME_PID, String::new()));
+  assert!(!gm.is_running("/games/reuse".to_string(), Some(true)));
+}
+
+#[tokio::test]
+async fn test_game_session() {
+  let mut gm = GameManager::new();
+  gm.replace_known_games(vec![known_game("game-id", "/games/session")]);
+  assert!(gm.get_session_summary("game-id").is_none());
+
+  gm.handle_process_message(ProcessMessage::started(
+    LAUNCHER_PID,
+    1,
+    "/games/session/launcher.exe".to_string(),
+  ));
+  gm.handle_process_message(ProcessMessage::started(
+    GAME_PID,
+    LAUNCHER_PID,
+    "/games/session/game.exe".to_string(),
+  ));
+  let summary = gm.get_session_summary("game-id").unwrap();
+  assert_eq!(summary.game_id, "game-id");
+  assert_eq!(summary.pids, vec![LAUNCHER_PID, GAME_PID]);
+  assert!(summary.end_time.is_none());
+
+  // foreground time is accumulated only while a game window is in foreground
+  gm.handle_foreground_message(GAME_PID);
+  tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+  gm.handle_foreground_message(UNRELATED_PID);
+  let foreground_time = gm.get_session_summary("game-id").unwrap().foreground_time;
+  assert!(foreground_time >= 50);
+  tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+  assert_eq!(
+    gm.get_session_summary("game-id").unwrap().foreground_time,
+    foreground_time
+  );
+
+  // the session lasts until the last process exits
+  gm.handle_process_message(ProcessMessage::terminated(LAUNCHER_PID, String::new()));
+  let running = gm.get_session_summary("game-id").unwrap();
+  assert_eq!(running.session_id, summary.session_id);
+  gm.handle_process_message(ProcessMessage::terminated(GAME_PID, String::new()));
+  assert!(gm.get_session_summary("game-id").is_none());
+
+  // a new process starts a new session
+  gm.handle_process_message(ProcessMessage::started(
+    GAME_PID,
+    1,
+    "/games/session/game.exe".to_string(),
+  ));
+  assert_ne!(
+    gm.get_session_summary("game-id").unwrap().session_id,
+    summary.session_id
+  );
+}
+
+#[tokio::test]
+async fn test_session_grace_period() {
+  let mut gm = GameManager::new();
+  gm.replace_known_games(vec![known_game("game-id", "/games/grace")]);
+  gm.set_grace_period(Some("game-id".to_string()), Duration::from_secs(60));
+
+  gm.handle_process_message(ProcessMessage::started(
+    GAME_PID,
+    1,
+    "/games/grace/game.exe".to_string(),
+  ));
+  let session_id = gm.get_session_summary("game-id").unwrap().session_id;
+
+  // a restart within the grace period continues the same session
+  gm.handle_process_message(ProcessMessage::terminated(GAME_PID, String::new()));
+  assert!(gm.get_session_summary("game-id").is_some());
+  gm.handle_process_message(ProcessMessage::started(
+    HELPER_PID,
+    1,
+    "/games/grace/game.exe".to_string(),
+  ));
+  let summary = gm.get_session_summary("game-id").unwrap();
+  assert_eq!(summary.session_id, session_id);
+  assert_eq!(summary.pids, vec![GAME_PID, HELPER_PID]);
+  // a stale expiry of a resumed session is ignored
+  gm.expire_session("game-id", &session_id);
+  assert!(gm.get_session_summary("game-id").is_some());
+
+  gm.handle_process_message(ProcessMessage::terminated(HELPER_PID, String::new()));
+  gm.expire_session("game-id", &session_id);
+  assert!(gm.get_session_summary("game-id").is_none());
+}
+
+#[test]
+fn test_crash_detection() {
+  let session = |start_time, end_time| SessionSummary {
+    session_id: "session-id".to_string(),
+    game_id: "game-id".to_string(),
+    start_time,
+    end_time: Some(end_time),
+    foreground_time: 0,
+    resources: None,
+    pids: vec![GAME_PID],
+  };
+  // failing right after launch is a crash, quitting normally is not
+  assert!(gm::is_crash(1, &session(0, 5_000)));
+  assert!(gm::is_crash(0xC000_0005, &session(0, 5_000)));
+  assert!(!gm::is_crash(0, &session(0, 5_000)));
+  // a failure after playing for a while is not reported as a crash
+  assert!(!gm::is_crash(1, &session(0, 3_600_000)));
+}
+
+#[test]
+fn test_resource_summary() {
+  let sample = |peak_memory, read_bytes| ProcessResources {
+    peak_memory: Some(peak_memory),
+    read_bytes: Some(read_bytes),
+    ..Default::default()
+  };
+  // a later sample replaces the counters but keeps the peak
+  let mut game = sample(800, 10);
+  game.update(&sample(500, 30));
+  assert_eq!(game, sample(800, 30));
+
+  // a session sums the counters of its processes and keeps the highest peak
+  let mut session = ProcessResources::default();
+  session.accumulate(&game);
+  session.accumulate(&ProcessResources {
+    peak_memory: Some(100),
+    hard_faults: Some(2),
+    ..Default::default()
+  });
+  assert_eq!(session.peak_memory, Some(800));
+  assert_eq!(session.read_bytes, Some(30));
+  assert_eq!(session.hard_faults, Some(2));
+  assert_eq!(session.cpu_cycles, None);
+}
+
+#[tokio::test]
+async fn test_matching_rules() {
+  let mut gm = GameManager::new();
+  gm.replace_known_games(vec![known_game("game-id", "/games/rules")]);
+  let rules = vec![
+    MatchingRule {
+      game_id: "game-id".to_string(),
+      exe_names: None,
+      path_prefixes: None,
+      cmdline_pattern: None,
+      parent_exe_names: None,
+      exclude_exe_names: Some(vec!["crash*".to_string()]),
+      min_lifetime: Some(60_000),
+    },
+    MatchingRule {
+      game_id: "emulated-id".to_string(),
+      exe_names: Some(vec!["emulator*".to_string()]),
+      path_prefixes: None,
+      cmdline_pattern: None,
+      parent_exe_names: None,
+      exclude_exe_names: None,
+      min_lifetime: None,
+    },
+  ];
+  gm.set_matching_rules(
+    rules
+      .into_iter()
+      .map(|rule| CompiledRule::compile(rule).unwrap())
+      .collect(),
+  );
+
+  // processes identified by an exe name glob
+  gm.handle_process_message(ProcessMessage::started(
+    UNRELATED_PID,
+    1,
+    "/usr/bin/emulator-x64".to_string(),
+  ));
+  assert_eq!(
+    gm.get_running_game_id(UNRELATED_PID),
+    Some("emulated-id".to_string())
+  );
+
+  // excluded processes are never attributed, even if they are children of the game
+  gm.handle_process_message(ProcessMessage::started(
+    LAUNCHER_PID,
+    1,
+    "/games/rules/crashreporter".to_string(),
+  ));
+  assert_eq!(gm.get_running_game_id(LAUNCHER_PID), None);
+
+  // short-lived processes are ignored
+  gm.handle_process_message(ProcessMessage::started(
+    GAME_PID,
+    1,
+    "/games/rules/game".to_string(),
+  ));
+  assert!(gm.get_session_summary("game-id").is_none());
+  gm.handle_process_message(ProcessMessage::terminated(GAME_PID, String::new()));
+  gm.confirm_pending_start(GAME_PID);
+  assert!(gm.get_session_summary("game-id").is_none());
+
+  // processes which lived long enough start the session
+  gm.handle_process_message(ProcessMessage::started(
+    GAME_PID,
+    1,
+    "/games/rules/game".to_string(),
+  ));
+  gm.handle_process_message(ProcessMessage::started(
+    HELPER_PID,
+    GAME_PID,
+    "/games/rules/crashpad".to_string(),
+  ));
+  gm.confirm_pending_start(GAME_PID);
+  let summary = gm.get_session_summary("game-id").unwrap();
+  assert_eq!(summary.pids, vec![GAME_PID]);
+}
+
+#[test]
+fn test_known_game_conflicts() {
+  let mut gm = GameManager::new();
+  let conflicts = gm
+    .replace_known_games(vec![
+      known_game("first-id", "game.exe"),
+      known_game("second-id", "Game.exe"),
+      known_game("third-id", "/games/rpg/game.exe"),
+    ])
+    .conflicts;
+  if cfg!(windows) {
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].key, "game.exe");
+    assert_eq!(conflicts[0].game_ids, vec!["first-id", "second-id"]);
+  } else {
+    // keys of other platforms are case sensitive
+    assert!(conflicts.is_empty());
+  }
+  let conflicts = gm
+    .add_known_game(known_game("fourth-id", "game.exe"))
+    .conflicts;
+  assert_eq!(conflicts.len(), 1);
+  assert_eq!(conflicts[0].key, "game.exe");
+  assert!(conflicts[0].game_ids.ends_with(&["fourth-id".to_string()]));
+  assert!(gm
+    .add_known_game(known_game("fifth-id", "/games/other"))
+    .conflicts
+    .is_empty());
+
+  // the most specific key decides, ambiguous matches are not attributed by guess
+  assert_eq!(
+    gm.get_known_game_id(&NormalizedPath::new("/games/rpg/game.exe")),
+    Some(KnownGameMatch::Unique("third-id".to_string()))
+  );
+  assert!(matches!(
+    gm.get_known_game_id(&NormalizedPath::new("/games/another/game.exe")),
+    Some(KnownGameMatch::Ambiguous(_))
+  ));
+  gm.handle_process_message(ProcessMessage::started(
+    GAME_PID,
+    1,
+    "/games/another/game.exe".to_string(),
+  ));
+  assert_eq!(gm.get_running_game_id(GAME_PID), None);
+
+  // unless its parent is running one of the candidates
+  gm.handle_process_message(ProcessMessage::started(
+    LAUNCHER_PID,
+    1,
+    "/games/rpg/game.exe".to_string(),
+  ));
+  gm.handle_process_message(ProcessMessage::started(
+    HELPER_PID,
+    LAUNCHER_PID,
+    "/games/another/game.exe".to_string(),
+  ));
+  assert_eq!(gm.get_running_game_id(HELPER_PID), None);
+  gm.remove_known_game_by_id("third-id", false);
+  // adding a game again replaces its keys
+  gm.add_known_game(KnownGame {
+    paths: vec!["game.exe".to_string(), "/games/rpg".to_string()],
+    ..known_game("fourth-id", "")
+  });
+  gm.handle_process_message(ProcessMessage::started(
+    LAUNCHER_PID,
+    1,
+    "/games/rpg/launcher".to_string(),
+  ));
+  gm.handle_process_message(ProcessMessage::started(
+    HELPER_PID,
+    LAUNCHER_PID,
+    "/games/another/game.exe".to_string(),
+  ));
+  assert_eq!(
+    gm.get_running_game_id(HELPER_PID),
+    Some("fourth-id".to_string())
+  );
+}
+
+#[test]
+fn test_patch_known_games() {
+  let mut gm = GameManager::new();
+  let update = gm.replace_known_games(vec![
+    known_game("rpg", "/games/rpg"),
+    known_game("tools", "/games/tools"),
+  ]);
+  assert_eq!(update.added, vec!["rpg", "tools"]);
+  gm.handle_process_message(ProcessMessage::started(
+    LAUNCHER_PID,
+    1,
+    "/games/rpg/launcher".to_string(),
+  ));
+  gm.handle_process_message(ProcessMessage::started(
+    GAME_PID,
+    LAUNCHER_PID,
+    "/games/shared/game".to_string(),
+  ));
+  assert_eq!(gm.get_running_game_id(GAME_PID), Some("rpg".to_string()));
+
+  // a process matching a new game by its own path moves to it
+  let update = gm.patch_known_games(KnownGamePatch {
+    upserts: Some(vec![
+      known_game("shared", "/games/shared"),
+      known_game("tools", "/games/tools"),
+    ]),
+    removes: None,
+  });
+  assert_eq!(update.added, vec!["shared"]);
+  assert!(update.updated.is_empty() && update.removed.is_empty());
+  assert_eq!(
+    update.reattributed,
+    vec![KnownGameReattribution {
+      pid: GAME_PID,
+      previous_id: Some("rpg".to_string()),
+      id: Some("shared".to_string()),
+    }]
+  );
+  assert!(gm.get_session_summary("rpg").is_some());
+  assert_eq!(
+    gm.get_session_summary("shared").unwrap().pids,
+    vec![GAME_PID]
+  );
+
+  // processes of a removed game are detached and its session ends
+  let update = gm.patch_known_games(KnownGamePatch {
+    upserts: None,
+    removes: Some(vec!["rpg".to_string(), "unknown".to_string()]),
+  });
+  assert_eq!(update.removed, vec!["rpg"]);
+  assert_eq!(
+    update.reattributed,
+    vec![KnownGameReattribution {
+      pid: LAUNCHER_PID,
+      previous_id: Some("rpg".to_string()),
+      id: None,
+    }]
+  );
+  assert!(gm.get_session_summary("rpg").is_none());
+  assert_eq!(gm.get_running_game_id(LAUNCHER_PID), None);
+
+  // replacing removes the games which are not given, unchanged games are kept as they are
+  let update = gm.replace_known_games(vec![known_game("tools", "/games/tools")]);
+  assert!(update.added.is_empty() && update.updated.is_empty());
+  assert_eq!(update.removed, vec!["shared"]);
+  assert!(gm.get_session_summary("shared").is_none());
+  let update = gm.replace_known_games(vec![KnownGame {
+    match_mode: Some(KnownGameMatchMode::Folder),
+    ..known_game("tools", "/games/tools")
+  }]);
+  assert_eq!(update.updated, vec!["tools"]);
+  assert!(update.reattributed.is_empty());
+}
+
+#[test]
+fn test_track_launched_tree() {
+  let mut gm = GameManager::new();
+  let started = |pid: u32, ppid: u32, path: &str| {
+    ProcessMessage::started(pid, ppid, path.to_string()).with_start_time(1_000 + i64::from(pid))
+  };
+  assert!(gm.track_launched_process("launched", started(LAUNCHER_PID, 1, "/opt/launcher")));
+  assert!(!gm.track_launched_process("launched", started(LAUNCHER_PID, 1, "/opt/launcher")));
+  let entry = |pid: u32, ppid: u32, start_time: i64| ProcessEntry {
+    pid,
+    ppid,
+    path: format!("/opt/launcher/{pid}"),
+    start_time,
+  };
+  let tracked = gm.track_descendants(
+    "launched",
+    LAUNCHER_PID,
+    Some(1_000 + i64::from(LAUNCHER_PID)),
+    vec![
+      // a grandchild is found through its parent, whatever the order of the list
+      entry(HELPER_PID, GAME_PID, 2_000 + i64::from(HELPER_PID)),
+      entry(GAME_PID, LAUNCHER_PID, 2_000 + i64::from(GAME_PID)),
+      // started before the launcher, its parent pid belongs to an earlier process
+      entry(UNRELATED_PID, LAUNCHER_PID, 1),
+    ],
+  );
+  assert_eq!(tracked, vec![GAME_PID, HELPER_PID]);
+  assert_eq!(gm.get_running_game_id(UNRELATED_PID), None);
+
+  // launched processes keep their game when known games change
+  gm.replace_known_games(vec![known_game("other", "/games/other")]);
+  assert_eq!(
+    gm.get_running_game_id(LAUNCHER_PID),
+    Some("launched".to_string())
+  );
+}
+
+#[test]
+fn test_process_group_session() {
+  let mut gm = GameManager::new();
+  gm.set_grace_period(Some("contained".to_string()), Duration::from_secs(60));
+  gm.track_launched_process(
+    "contained",
+    ProcessMessage::started(LAUNCHER_PID, 1, "/opt/launcher".to_string()),
+  );
+  let group = Arc::new(FakeGroup(Mutex::new(vec![LAUNCHER_PID, HELPER_PID])));
+  assert!(gm.add_process_group("contained", group.clone()));
+  assert!(!gm.add_process_group("contained", Arc::new(FakeGroup(Mutex::new(Vec::new())))));
+  // a process in the group belongs to the game even if it is not tracked
+  assert_eq!(
+    gm.get_game_pids("contained"),
+    vec![LAUNCHER_PID, HELPER_PID]
+  );
+  assert!(gm.sync_process_groups("contained"));
+
+  // the session ends once the group is empty, the game is not restarting
+  group.0.lock().unwrap().clear();
+  gm.handle_process_message(ProcessMessage::terminated(
+    LAUNCHER_PID,
+    "/opt/launcher".to_string(),
+  ));
+  assert!(gm.get_session_summary("contained").is_none());
+  assert!(gm.get_game_pids("contained").is_empty());
+  assert!(!gm.sync_process_groups("contained"));
+}
@@ -0,0 +1,247 @@
+use crate::{
+  monitor::{
+    gm::GameManager,
+    known_games::{KnownGameMatch, KnownGames},
+    ProcessMessage,
+  },
+  napi_monitor::{KnownGame, KnownGameError, KnownGameMatchMode},
+  tests::known_game,
+  utils::path::NormalizedPath,
+};
+
+fn unique(id: &str) -> Option<KnownGameMatch> {
+  Some(KnownGameMatch::Unique(id.to_string()))
+}
+
+fn path(path: &str) -> NormalizedPath {
+  NormalizedPath::new(path)
+}
+
+#[test]
+fn test_longest_prefix_lookup() {
+  let mut games = KnownGames::default();
+  games.insert(path("/games"), "library".to_string(), None);
+  games.insert(path("/games/rpg"), "rpg".to_string(), None);
+  games.insert(path("/games/rpg/tools/editor"), "editor".to_string(), None);
+  games.insert(path(r"d:\games\vn"), "vn".to_string(), None);
+  games.insert(path("game.exe"), "bare".to_string(), None);
+
+  // any descendant of a folder matches, the nearest folder wins
+  assert_eq!(
+    games.lookup(&path("/games/rpg/bin/x64/game")),
+    unique("rpg")
+  );
+  assert_eq!(
+    games.lookup(&path("/games/rpg/tools/editor")),
+    unique("editor")
+  );
+  assert_eq!(games.lookup(&path("/games/other/game")), unique("library"));
+  assert_eq!(
+    games.lookup(&path(r"d:\games\vn\bin\x64\vn.exe")),
+    unique("vn")
+  );
+  // components are compared as a whole
+  assert_eq!(games.lookup(&path("/gamesdata/game")), None);
+  assert_eq!(games.lookup(&path(r"d:\games\vn2\vn.exe")), None);
+  // bare executable names are the last resort
+  assert_eq!(games.lookup(&path("/opt/game.exe")), unique("bare"));
+  assert_eq!(games.lookup(&path("game.exe")), unique("bare"));
+
+  games.remove_game("rpg");
+  assert_eq!(
+    games.lookup(&path("/games/rpg/bin/x64/game")),
+    unique("library")
+  );
+  games.remove_game("library");
+  assert_eq!(games.lookup(&path("/games/rpg/bin/x64/game")), None);
+  assert_eq!(
+    games.lookup(&path("/games/rpg/tools/editor/run")),
+    unique("editor")
+  );
+}
+
+#[test]
+fn test_lookup_many_games() {
+  let mut games = KnownGames::default();
+  for i in 0..50_000 {
+    games.insert(
+      NormalizedPath::new(format!("/games/{i}")),
+      format!("game-{i}"),
+      None,
+    );
+  }
+  assert_eq!(
+    games.lookup(&path("/games/42424/bin/game")),
+    unique("game-42424")
+  );
+  assert!(games.get_shared_keys().is_empty());
+  games.insert(path("/games/7"), "duplicate".to_string(), None);
+  assert_eq!(
+    games.get_shared_keys(),
+    vec![(
+      "/games/7".to_string(),
+      vec!["game-7".to_string(), "duplicate".to_string()]
+    )]
+  );
+}
+
+#[test]
+fn test_match_modes() {
+  let mut games = KnownGames::default();
+  games.insert(
+    path("/games/rpg/game"),
+    "file".to_string(),
+    Some(KnownGameMatchMode::File),
+  );
+  games.insert(
+    path("/games/flat"),
+    "folder".to_string(),
+    Some(KnownGameMatchMode::Folder),
+  );
+  games.insert(
+    path("/games"),
+    "recursive".to_string(),
+    Some(KnownGameMatchMode::FolderRecursive),
+  );
+  games.insert(
+    path("/opt/launcher/start"),
+    "exe-name".to_string(),
+    Some(KnownGameMatchMode::ExeName),
+  );
+
+  assert_eq!(games.lookup(&path("/games/rpg/game")), unique("file"));
+  // a file key does not match below it, the recursive folder above does
+  assert_eq!(
+    games.lookup(&path("/games/rpg/game/helper")),
+    unique("recursive")
+  );
+  assert_eq!(games.lookup(&path("/games/flat/game")), unique("folder"));
+  assert_eq!(
+    games.lookup(&path("/games/flat/bin/game")),
+    unique("recursive")
+  );
+  assert_eq!(games.lookup(&path("/games/flat")), unique("recursive"));
+  assert_eq!(games.lookup(&path("/games")), unique("recursive"));
+  assert_eq!(games.lookup(&path("/usr/bin/start")), unique("exe-name"));
+  assert_eq!(games.lookup(&path("/opt/launcher/other")), None);
+}
+
+#[test]
+fn test_known_game_validation() {
+  let mut gm = GameManager::new();
+  let update = gm.replace_known_games(vec![
+    known_game("", "/games/a"),
+    KnownGame {
+      paths: Vec::new(),
+      ..known_game("no-paths", "")
+    },
+    known_game("empty-path", " "),
+    KnownGame {
+      match_mode: Some(KnownGameMatchMode::Folder),
+      ..known_game("bare-folder", "game.exe")
+    },
+    KnownGame {
+      paths: vec!["/games/b".to_string(), "steam:570".to_string()],
+      match_mode: Some(KnownGameMatchMode::FolderRecursive),
+      ..known_game("valid", "")
+    },
+    known_game("valid", "/games/c"),
+    KnownGame {
+      enabled: Some(false),
+      ..known_game("disabled", "/games/d")
+    },
+  ]);
+  let rejected: Vec<(u32, &str)> = update
+    .errors
+    .iter()
+    .map(|error| (error.index, error.id.as_str()))
+    .collect();
+  assert_eq!(
+    rejected,
+    vec![
+      (0, ""),
+      (1, "no-paths"),
+      (2, "empty-path"),
+      (3, "bare-folder"),
+      (5, "valid")
+    ]
+  );
+  assert_eq!(
+    update.errors[4].reason,
+    "duplicate id, first given at index 4"
+  );
+  assert_eq!(
+    gm.get_known_game_id(&path("/games/b/game")),
+    unique("valid")
+  );
+  assert_eq!(gm.get_known_game_id(&path("/games/c/game")), None);
+  assert_eq!(gm.get_known_game_id(&path("/games/d/game")), None);
+
+  // an invalid game leaves the known game it would replace in place
+  let update = gm.add_known_game(known_game("valid", ""));
+  assert_eq!(
+    update.errors,
+    vec![KnownGameError {
+      id: "valid".to_string(),
+      index: 0,
+      reason: "a path is empty".to_string(),
+    }]
+  );
+  assert_eq!(
+    gm.get_known_game_id(&path("/games/b/game")),
+    unique("valid")
+  );
+  // disabling a game removes it
+  gm.add_known_game(KnownGame {
+    enabled: Some(false),
+    ..known_game("valid", "/games/b")
+  });
+  assert_eq!(gm.get_known_game_id(&path("/games/b/game")), None);
+}
+
+#[test]
+fn test_is_running_descendant() {
+  let mut gm = GameManager::new();
+  gm.replace_known_games(vec![known_game("game-id", "/games/nested")]);
+  gm.handle_process_message(ProcessMessage::started(
+    5_000_101,
+    1,
+    "/games/nested/bin/x64/game".to_string(),
+  ));
+  assert_eq!(
+    gm.get_running_game_id(5_000_101),
+    Some("game-id".to_string())
+  );
+  assert!(gm.is_running("/games/nested".to_string(), Some(true)));
+  assert!(gm.is_running("/games/nested/bin".to_string(), Some(true)));
+  assert!(!gm.is_running("/games/nest".to_string(), Some(true)));
+  assert!(gm.is_running("/games/nested/bin/x64/game".to_string(), None));
+  assert!(!gm.is_running("/games/nested".to_string(), None));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlinked_game_folder() {
+  let dir = std::env::temp_dir().join(format!("vnite-test-symlink-{}", std::process::id()));
+  let target = dir.join("library/game");
+  let link = dir.join("game");
+  std::fs::create_dir_all(&target).unwrap();
+  std::os::unix::fs::symlink(&target, &link).unwrap();
+  let real_exe = std::fs::canonicalize(&target).unwrap().join("game");
+
+  let mut gm = GameManager::new();
+  gm.replace_known_games(vec![known_game("linked", &link.to_string_lossy())]);
+  // processes report their resolved image path
+  assert_eq!(
+    gm.get_known_game_id(&NormalizedPath::new(real_exe.to_string_lossy())),
+    unique("linked")
+  );
+
+  gm.set_resolve_symlinks(false);
+  gm.replace_known_games(vec![known_game("linked", &link.to_string_lossy())]);
+  assert_eq!(
+    gm.get_known_game_id(&NormalizedPath::new(real_exe.to_string_lossy())),
+    None
+  );
+  std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use crate::{
+  launcher,
+  monitor::gm,
+  napi_launcher::{LaunchErrorKind, LaunchOptions},
+  platform::linux::cgroup::Cgroup,
+};
+
+fn shell(script: &str) -> LaunchOptions {
+  LaunchOptions {
+    path: "/bin/sh".to_string(),
+    args: Some(vec!["-c".to_string(), script.to_string()]),
+    ..Default::default()
+  }
+}
+
+#[tokio::test]
+async fn test_launch_failures() {
+  let error = launcher::launch_game(
+    "launch-missing".to_string(),
+    LaunchOptions {
+      path: "/nonexistent/vnite/game".to_string(),
+      ..Default::default()
+    },
+  )
+  .await
+  .unwrap_err();
+  assert_eq!(error.kind, LaunchErrorKind::NotFound);
+
+  let error = launcher::launch_game("launch-crash".to_string(), shell("exit 3"))
+    .await
+    .unwrap_err();
+  assert_eq!(error.kind, LaunchErrorKind::EarlyExit);
+  assert_eq!(error.exit_code, Some(3));
+
+  // a launcher handing over to the game exits successfully
+  assert!(
+    launcher::launch_game("launch-handover".to_string(), shell("exit 0"))
+      .await
+      .is_ok()
+  );
+}
+
+#[tokio::test]
+async fn test_launch_tracks_process() {
+  let pid = launcher::launch_game(
+    "launch-running".to_string(),
+    LaunchOptions {
+      early_exit_window: Some(0),
+      ..shell("sleep 0.5")
+    },
+  )
+  .await
+  .unwrap();
+  // tracked right away, although the path matches no known game
+  {
+    let gm = gm::get().lock().await;
+    assert_eq!(
+      gm.get_running_game_id(pid),
+      Some("launch-running".to_string())
+    );
+    assert_eq!(
+      gm.get_session_summary("launch-running").unwrap().pids,
+      vec![pid]
+    );
+  }
+
+  // the exit is reported by the launcher without a monitor
+  tokio::time::sleep(Duration::from_millis(1500)).await;
+  let gm = gm::get().lock().await;
+  assert_eq!(gm.get_running_game_id(pid), None);
+  assert!(gm.get_session_summary("launch-running").is_none());
+}
+
+#[tokio::test]
+async fn test_launch_contains_detached_process() {
+  // containment needs a writable cgroup, which only some environments provide
+  if Cgroup::create("probe").is_err() {
+    return;
+  }
+  // the shell exits right away, leaving its child behind
+  let pid = launcher::launch_game(
+    "launch-detached".to_string(),
+    LaunchOptions {
+      early_exit_window: Some(0),
+      ..shell("sleep 2 & exit 0")
+    },
+  )
+  .await
+  .unwrap();
+
+  tokio::time::sleep(Duration::from_millis(500)).await;
+  {
+    let gm = gm::get().lock().await;
+    let pids = gm.get_game_pids("launch-detached");
+    assert_eq!(pids.len(), 1);
+    assert_ne!(pids[0], pid);
+    let summary = gm.get_session_summary("launch-detached").unwrap();
+    assert_eq!(summary.pids, vec![pid, pids[0]]);
+  }
+
+  // the session ends once the child exits, although no monitor reports it
+  tokio::time::sleep(Duration::from_millis(3000)).await;
+  let gm = gm::get().lock().await;
+  assert!(gm.get_game_pids("launch-detached").is_empty());
+  assert!(gm.get_session_summary("launch-detached").is_none());
+}
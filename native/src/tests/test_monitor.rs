@@ -1,29 +1,112 @@
+use std::{
+  process::{Command, Stdio},
+  time::Duration,
+};
+
+#[cfg(windows)]
 use crate::foreground;
-use crate::monitor;
+use crate::{
+  monitor::{self, polling_monitor::polling::Polling},
+  napi_monitor::{MonitorBackend, MonitorBackendPreference, MonitorOptions},
+  tests::known_game,
+};
 
 #[tokio::test]
 async fn test_monitor() {
-  monitor::start_monitoring(
-    vec![r"c:\program files\cmake\bin\cmake-gui.exe".to_string()], 
-    vec!["123456-abcdef".to_string()],
-    None
+  let status = monitor::start_monitoring(
+    vec![known_game("123456-abcdef", r"c:\program files\cmake\bin\cmake-gui.exe")],
+    None,
+    monitor::MonitorConfig::default(),
   ).await;
+  assert!(status.backend.is_some());
   tokio::time::sleep(tokio::time::Duration::from_secs(20)).await;
   let is_running = monitor::gm::get().lock().await.is_running("C:\\Program Files\\CMake\\bin".to_string(), Some(true));
   println!("is running: {}", is_running);
   monitor::stop_monitoring().await;
 }
 
+#[cfg(windows)]
 #[tokio::test]
 async fn test_foreground_hook() {
   monitor::start_monitoring(
-    vec![r"c:\program files\cmake\bin\cmake-gui.exe".to_string()], 
-    vec!["123456-abcdef".to_string()],
-    None
+    vec![known_game("123456-abcdef", r"c:\program files\cmake\bin\cmake-gui.exe")],
+    None,
+    monitor::MonitorConfig::default(),
   ).await;
-  foreground::install_hook(None, None).await;
+  foreground::install_hook(None, None).await.unwrap();
 
   tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
   foreground::uninstall_hook().await;
   monitor::stop_monitoring().await;
 }
+
+#[test]
+fn test_monitor_options() {
+  let config = monitor::MonitorConfig::try_from(MonitorOptions {
+    backend: Some(MonitorBackendPreference::Wmi),
+    polling_interval: Some(1000),
+    startup_scan: Some(false),
+    startup_scan_delay: None,
+    resolve_symlinks: None,
+  })
+  .unwrap();
+  assert_eq!(
+    config.fallback_chain(),
+    vec![MonitorBackend::Wmi, MonitorBackend::Polling]
+  );
+  assert_eq!(config.polling_interval, Duration::from_secs(1));
+  assert!(!config.startup_scan);
+  assert_eq!(config.startup_scan_delay, Duration::from_secs(10));
+
+  let config = monitor::MonitorConfig::try_from(MonitorOptions {
+    backend: Some(MonitorBackendPreference::Polling),
+    polling_interval: None,
+    startup_scan: None,
+    startup_scan_delay: None,
+    resolve_symlinks: None,
+  })
+  .unwrap();
+  assert!(config.resolve_symlinks);
+  assert_eq!(config.fallback_chain(), vec![MonitorBackend::Polling]);
+  assert!(config.startup_scan);
+
+  assert!(monitor::MonitorConfig::try_from(MonitorOptions {
+    backend: None,
+    polling_interval: Some(10),
+    startup_scan: None,
+    startup_scan_delay: None,
+    resolve_symlinks: None,
+  })
+  .is_err());
+}
+
+#[tokio::test]
+async fn test_polling_startup_scan() {
+  // the first poll reports every running process with a startup scan
+  let (tx, mut rx) = tokio::sync::mpsc::channel(4096);
+  let mut polling = Polling::new(tx, true);
+  polling.update_process_status().await;
+  let mut pids = Vec::new();
+  while let Ok(msg) = rx.try_recv() {
+    pids.push(msg.identity().pid);
+  }
+  assert!(pids.contains(&std::process::id()));
+
+  // and only caches them without
+  let (tx, mut rx) = tokio::sync::mpsc::channel(4096);
+  let mut polling = Polling::new(tx, false);
+  polling.update_process_status().await;
+  assert!(rx.try_recv().is_err());
+
+  // processes started since then are reported
+  let mut child = Command::new("sort").stdin(Stdio::piped()).spawn().unwrap();
+  polling.update_process_status().await;
+  let mut pids = Vec::new();
+  while let Ok(msg) = rx.try_recv() {
+    pids.push(msg.identity().pid);
+  }
+  child.kill().unwrap();
+  child.wait().unwrap();
+  assert!(pids.contains(&child.id()));
+  assert!(!pids.contains(&std::process::id()));
+}
@@ -0,0 +1,87 @@
+use crate::platform::linux::proc_connector::{self, ProcEvent};
+
+// Build a proc connector datagram: nlmsghdr + cn_msg + proc_event header + event data
+fn build_event(what: u32, event_data: &[u32]) -> Vec<u8> {
+  let mut payload = Vec::new();
+  // struct cn_msg (idx, val, seq, ack, len, flags)
+  for v in [1u32, 1, 0, 0] {
+    payload.extend_from_slice(&v.to_ne_bytes());
+  }
+  payload.extend_from_slice(&((16 + event_data.len() * 4) as u16).to_ne_bytes());
+  payload.extend_from_slice(&0u16.to_ne_bytes());
+  // struct proc_event (what, cpu, timestamp_ns)
+  payload.extend_from_slice(&what.to_ne_bytes());
+  payload.extend_from_slice(&0u32.to_ne_bytes());
+  payload.extend_from_slice(&0u64.to_ne_bytes());
+  for v in event_data {
+    payload.extend_from_slice(&v.to_ne_bytes());
+  }
+
+  let mut message = Vec::new();
+  message.extend_from_slice(&((16 + payload.len()) as u32).to_ne_bytes());
+  message.extend_from_slice(&3u16.to_ne_bytes());
+  message.extend_from_slice(&0u16.to_ne_bytes());
+  message.extend_from_slice(&0u32.to_ne_bytes());
+  message.extend_from_slice(&0u32.to_ne_bytes());
+  message.extend_from_slice(&payload);
+  message
+}
+
+#[test]
+fn test_parse_exec_and_exit() {
+  let exec = build_event(0x2, &[4321, 4321]);
+  assert_eq!(
+    proc_connector::parse_messages(&exec),
+    vec![ProcEvent::Exec { pid: 4321 }]
+  );
+
+  let exit = build_event(0x8000_0000, &[4321, 4321, 0, 17]);
+  assert_eq!(
+    proc_connector::parse_messages(&exit),
+    vec![ProcEvent::Exit {
+      pid: 4321,
+      exit_code: 0
+    }]
+  );
+  // exited with status 3
+  let exit = build_event(0x8000_0000, &[4321, 4321, 3 << 8, 17]);
+  assert_eq!(
+    proc_connector::parse_messages(&exit),
+    vec![ProcEvent::Exit {
+      pid: 4321,
+      exit_code: 3
+    }]
+  );
+
+  // a non-leader thread exiting is not a process exit
+  let thread_exit = build_event(0x8000_0000, &[4322, 4321, 0, 0]);
+  assert!(proc_connector::parse_messages(&thread_exit).is_empty());
+
+  // fork events are ignored
+  let fork = build_event(0x1, &[1, 1, 4321, 4321]);
+  assert!(proc_connector::parse_messages(&fork).is_empty());
+}
+
+#[test]
+fn test_wait_status_to_exit_code() {
+  assert_eq!(proc_connector::wait_status_to_exit_code(0), 0);
+  assert_eq!(proc_connector::wait_status_to_exit_code(1 << 8), 1);
+  // killed by SIGSEGV, with or without a core dump
+  assert_eq!(proc_connector::wait_status_to_exit_code(11), 139);
+  assert_eq!(proc_connector::wait_status_to_exit_code(0x80 | 11), 139);
+}
+
+#[test]
+fn test_parse_truncated_message() {
+  let exec = build_event(0x2, &[4321, 4321]);
+  assert!(proc_connector::parse_messages(&exec[..exec.len() - 2]).is_empty());
+  assert!(proc_connector::parse_messages(&[]).is_empty());
+}
+
+#[test]
+fn test_build_control_message() {
+  let message = proc_connector::build_control_message(1);
+  assert_eq!(message.len(), 40);
+  assert_eq!(&message[0..4], &40u32.to_ne_bytes());
+  assert_eq!(&message[36..40], &1u32.to_ne_bytes());
+}
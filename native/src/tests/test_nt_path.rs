@@ -0,0 +1,90 @@
+use crate::utils::nt_path::DriveMappings;
+
+fn mappings() -> DriveMappings {
+  DriveMappings::new(
+    [
+      ("C:", r"\Device\HarddiskVolume3"),
+      ("D:", r"\Device\HarddiskVolume1"),
+      ("E:", r"\Device\HarddiskVolume10"),
+      ("S:", r"\??\C:\Games"),
+      ("T:", r"\??\UNC\nas\games"),
+      (
+        "Z:",
+        r"\Device\LanmanRedirector\;Z:0000000000012345\nas\share",
+      ),
+    ]
+    .map(|(dos, nt)| (dos.to_string(), nt.to_string())),
+  )
+}
+
+#[test]
+fn test_translate_volume() {
+  let mappings = mappings();
+  assert_eq!(
+    mappings.to_dos_paths(r"\Device\HarddiskVolume3\Program Files\game.exe"),
+    vec![r"C:\Program Files\game.exe"]
+  );
+  // devices are compared as whole components, volume 1 is not a prefix of volume 10
+  assert_eq!(
+    mappings.to_dos_paths(r"\Device\HarddiskVolume10\game.exe"),
+    vec![r"E:\game.exe"]
+  );
+  assert_eq!(
+    mappings.to_dos_paths(r"\device\harddiskvolume1\game.exe"),
+    vec![r"D:\game.exe"]
+  );
+  assert!(mappings
+    .to_dos_paths(r"\Device\HarddiskVolume7\game.exe")
+    .is_empty());
+  assert!(DriveMappings::default()
+    .to_dos_paths(r"\Device\HarddiskVolume3\game.exe")
+    .is_empty());
+}
+
+#[test]
+fn test_translate_subst() {
+  // the most specific drive first
+  assert_eq!(
+    mappings().to_dos_paths(r"\Device\HarddiskVolume3\Games\RPG\game.exe"),
+    vec![r"S:\RPG\game.exe", r"C:\Games\RPG\game.exe"]
+  );
+  assert_eq!(
+    mappings().to_dos_paths(r"\Device\HarddiskVolume3\GamesData\game.exe"),
+    vec![r"C:\GamesData\game.exe"]
+  );
+}
+
+#[test]
+fn test_translate_network() {
+  let mappings = mappings();
+  assert_eq!(
+    mappings.to_dos_paths(r"\Device\Mup\nas\share\game.exe"),
+    vec![r"Z:\game.exe", r"\\nas\share\game.exe"]
+  );
+  assert_eq!(
+    mappings.to_dos_paths(r"\Device\Mup\;LanmanRedirector\;Z:0000000000012345\nas\share\game.exe"),
+    vec![r"Z:\game.exe", r"\\nas\share\game.exe"]
+  );
+  assert_eq!(
+    mappings.to_dos_paths(r"\Device\Mup\nas\games\game.exe"),
+    vec![r"T:\game.exe", r"\\nas\games\game.exe"]
+  );
+  // unmapped shares are reachable with their UNC path
+  assert_eq!(
+    mappings.to_dos_paths(r"\Device\Mup\server\other\game.exe"),
+    vec![r"\\server\other\game.exe"]
+  );
+}
+
+#[test]
+fn test_translate_dos_prefix() {
+  let mappings = DriveMappings::default();
+  assert_eq!(
+    mappings.to_dos_paths(r"\??\C:\Games\game.exe"),
+    vec![r"C:\Games\game.exe"]
+  );
+  assert_eq!(
+    mappings.to_dos_paths(r"\??\UNC\nas\share\game.exe"),
+    vec![r"\\nas\share\game.exe"]
+  );
+}
@@ -0,0 +1,111 @@
+use crate::utils::path::NormalizedPath;
+
+fn normalize(path: &str) -> String {
+  NormalizedPath::new(path).into_string()
+}
+
+#[test]
+fn test_normalize_separators() {
+  assert_eq!(normalize(r"D:/Game//Game.exe/"), r"d:\game\game.exe");
+  assert_eq!(
+    normalize(r"\\Server\Share\\Games\"),
+    r"\\server\share\games"
+  );
+  assert_eq!(normalize("/mnt//Games/"), "/mnt/Games");
+  assert_eq!(normalize("/"), "/");
+  assert_eq!(normalize("game.exe"), "game.exe");
+}
+
+#[test]
+fn test_normalize_case() {
+  // windows paths are folded beyond ASCII, host paths are case sensitive
+  assert_eq!(
+    normalize(r"D:\ゲーム\ÄÖÜ\Ｇａｍｅ.exe"),
+    r"d:\ゲーム\äöü\ｇａｍｅ.exe"
+  );
+  if !cfg!(windows) {
+    assert_eq!(normalize("/opt/ゲーム/Game"), "/opt/ゲーム/Game");
+  }
+}
+
+#[test]
+fn test_normalize_long_path_prefix() {
+  assert_eq!(normalize(r"\\?\C:\Games\Game.exe"), r"c:\games\game.exe");
+  assert_eq!(normalize(r"\??\C:\Games\Game.exe"), r"c:\games\game.exe");
+  assert_eq!(
+    normalize(r"\\?\UNC\server\share\game.exe"),
+    r"\\server\share\game.exe"
+  );
+}
+
+#[test]
+fn test_normalize_env_vars() {
+  std::env::set_var("VNITE_TEST_GAMES", r"D:\Games");
+  assert_eq!(normalize(r"%VNITE_TEST_GAMES%\RPG"), r"d:\games\rpg");
+  // undefined variables are kept as they are
+  assert_eq!(
+    normalize(r"C:\%VNITE_TEST_UNDEFINED%\game.exe"),
+    r"c:\%vnite_test_undefined%\game.exe"
+  );
+  if !cfg!(windows) {
+    std::env::set_var("VNITE_TEST_ROOT", "/mnt/games");
+    assert_eq!(normalize("$VNITE_TEST_ROOT/rpg"), "/mnt/games/rpg");
+    assert_eq!(normalize("${VNITE_TEST_ROOT}/rpg/"), "/mnt/games/rpg");
+    assert_eq!(
+      normalize("/opt/$VNITE_TEST_UNDEFINED/game"),
+      "/opt/$VNITE_TEST_UNDEFINED/game"
+    );
+  }
+}
+
+#[test]
+fn test_path_components() {
+  let path = NormalizedPath::new("/games/rpg//game");
+  assert_eq!(
+    path.components().collect::<Vec<_>>(),
+    vec!["", "games", "rpg", "game"]
+  );
+  assert_eq!(path.file_name(), "game");
+  let path = NormalizedPath::new(r"D:\Games\RPG");
+  assert_eq!(
+    path.components().collect::<Vec<_>>(),
+    vec!["d:", "games", "rpg"]
+  );
+  assert_eq!(path.file_name(), "rpg");
+  let path = NormalizedPath::new("/");
+  assert_eq!(path.components().collect::<Vec<_>>(), vec![""]);
+  assert!(!NormalizedPath::new("game.exe").is_path());
+}
+
+#[test]
+fn test_is_under() {
+  let dir = NormalizedPath::new(r"D:\Games\");
+  assert!(NormalizedPath::new(r"d:/games/rpg/game.exe").is_under(&dir));
+  assert!(NormalizedPath::new(r"d:\games").is_under(&dir));
+  assert!(!NormalizedPath::new(r"d:\gamesdata\game.exe").is_under(&dir));
+  assert!(NormalizedPath::new("/opt/game").is_under(&NormalizedPath::new("/")));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_resolve_symlinks() {
+  let dir = std::env::temp_dir().join(format!("vnite-test-path-{}", std::process::id()));
+  let target = dir.join("target");
+  let link = dir.join("link");
+  std::fs::create_dir_all(&target).unwrap();
+  std::os::unix::fs::symlink(&target, &link).unwrap();
+
+  let resolved = NormalizedPath::new(link.to_string_lossy()).resolve_symlinks();
+  assert_eq!(
+    resolved,
+    Some(NormalizedPath::new(
+      std::fs::canonicalize(&target).unwrap().to_string_lossy()
+    ))
+  );
+  assert_eq!(
+    NormalizedPath::new(dir.join("missing").to_string_lossy()).resolve_symlinks(),
+    None
+  );
+  assert_eq!(NormalizedPath::new("game.exe").resolve_symlinks(), None);
+  std::fs::remove_dir_all(&dir).unwrap();
+}
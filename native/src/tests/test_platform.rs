@@ -0,0 +1,111 @@
+use std::{os::unix::process::CommandExt, path::Path, process::Command, time::Duration};
+
+use crate::platform::{
+  self,
+  linux::{
+    cgroup, procfs,
+    subreaper::{self, ProcessTree},
+  },
+  Notification, PlatformError,
+};
+
+#[test]
+fn test_parse_effective_capabilities() {
+  let status = "Name:\tvnite\nCapInh:\t0000000000000000\nCapEff:\t000001ffffffffff\n";
+  assert_eq!(
+    procfs::parse_effective_capabilities(status),
+    Some(0x000001ffffffffff)
+  );
+  assert_eq!(procfs::parse_effective_capabilities("Name:\tvnite\n"), None);
+}
+
+#[test]
+fn test_parse_cgroup() {
+  let mounts = "sysfs /sys sysfs rw 0 0\n\
+    cgroup /sys/fs/cgroup/memory cgroup rw,memory 0 0\n\
+    cgroup2 /sys/fs/cgroup/unified cgroup2 rw,nsdelegate 0 0\n";
+  assert_eq!(
+    cgroup::find_cgroup2_mount(mounts),
+    Some("/sys/fs/cgroup/unified")
+  );
+  assert_eq!(
+    cgroup::find_cgroup2_mount("sysfs /sys sysfs rw 0 0\n"),
+    None
+  );
+
+  let content =
+    "4:memory:/user.slice\n0::/user.slice/user-1000.slice/user@1000.service/app.slice\n";
+  assert_eq!(
+    cgroup::parse_unified_cgroup(content),
+    Some("/user.slice/user-1000.slice/user@1000.service/app.slice")
+  );
+  assert_eq!(cgroup::parse_unified_cgroup("4:memory:/user.slice\n"), None);
+
+  let name = cgroup::cgroup_name("games/a b", 3);
+  assert!(name.starts_with("vnite-games_a_b-"));
+  assert!(name.ends_with("-3"));
+}
+
+#[test]
+fn test_process_tree_keeps_orphans() {
+  let tree = ProcessTree::new().unwrap();
+  let mut command = Command::new("/bin/sh");
+  // the subshell exits right away, leaving its child behind
+  command.args(["-c", "(sleep 2 &); sleep 0.5"]);
+  unsafe {
+    command.pre_exec(subreaper::set_child_subreaper);
+  }
+  let mut child = command.spawn().unwrap();
+  tree.attach(child.id()).unwrap();
+  let find_orphan = |pids: &[u32]| {
+    pids
+      .iter()
+      .copied()
+      .find(|pid| procfs::read_cmdline(*pid) == ["sleep", "2"])
+  };
+
+  // the orphan is reparented to the launched process while it runs
+  std::thread::sleep(Duration::from_millis(200));
+  let orphan = find_orphan(&tree.pids()).unwrap();
+  assert_eq!(procfs::get_parent_pid(orphan), child.id());
+
+  // and to current process after it exits
+  child.wait().unwrap();
+  let pids = tree.pids();
+  assert!(!pids.contains(&child.id()));
+  assert_eq!(find_orphan(&pids), Some(orphan));
+  assert_eq!(procfs::get_parent_pid(orphan), std::process::id());
+
+  // the orphan is reaped once it exits
+  std::thread::sleep(Duration::from_millis(2000));
+  assert_eq!(find_orphan(&tree.pids()), None);
+  assert!(!Path::new(&format!("/proc/{orphan}")).exists());
+}
+
+#[test]
+fn test_is_elevated_privilege() {
+  let is_root = unsafe { libc::geteuid() } == 0;
+  // root always has CAP_NET_ADMIN unless it has been explicitly dropped
+  if !is_root {
+    assert!(!platform::is_elevated_privilege());
+  }
+}
+
+#[test]
+fn test_unsupported_capabilities() {
+  let result = platform::send_notification(Notification {
+    app_id: "vnite".to_string(),
+    ..Default::default()
+  });
+  assert!(matches!(result, Err(PlatformError::Unsupported(_))));
+  assert_eq!(
+    result.unwrap_err().to_string(),
+    "system notification is not supported on linux"
+  );
+
+  let (tx, _rx) = tokio::sync::mpsc::channel::<u32>(1);
+  assert!(matches!(
+    platform::install_foreground_hook(tx),
+    Err(PlatformError::Unsupported(_))
+  ));
+}
@@ -0,0 +1,97 @@
+use crate::platform::{self, linux::procfs};
+
+#[test]
+fn test_parse_stat() {
+  let content = "1234 (game (x64).exe) S 1200 1234 1200 0 -1 4194560 2051 0 0 0 15 3 0 0 20 0 4 0 987654 123456789 4321 18446744073709551615 1 1 0 0 0 0 0 4096 0 0 0 0 17 3 0 0 0 0 0";
+  let stat = procfs::parse_stat(content).unwrap();
+  assert_eq!(stat.comm, "game (x64).exe");
+  assert_eq!(stat.state, 'S');
+  assert_eq!(stat.ppid, 1200);
+  assert_eq!(stat.major_faults, 0);
+  assert_eq!((stat.user_ticks, stat.system_ticks), (15, 3));
+  assert_eq!(stat.start_ticks, 987654);
+
+  assert!(procfs::parse_stat("1234 (truncated").is_none());
+  assert!(procfs::parse_stat("1234 (short) S 1").is_none());
+}
+
+#[test]
+fn test_parse_io() {
+  let content = "rchar: 323934931\nwchar: 323929600\nsyscr: 632687\nsyscw: 632675\nread_bytes: 4096\nwrite_bytes: 323932160\ncancelled_write_bytes: 0\n";
+  let io = procfs::parse_io(content).unwrap();
+  assert_eq!((io.read_operations, io.write_operations), (632687, 632675));
+  assert_eq!((io.read_bytes, io.write_bytes), (4096, 323932160));
+  assert!(procfs::parse_io("rchar: 1\nwchar: 2\n").is_none());
+}
+
+#[test]
+fn test_parse_peak_resident_size() {
+  let status = "Name:\tgame.exe\nVmPeak:\t 8458240 kB\nVmHWM:\t  524288 kB\nVmRSS:\t  262144 kB\n";
+  assert_eq!(
+    procfs::parse_peak_resident_size(status),
+    Some(512 * 1024 * 1024)
+  );
+  // the memory of an exiting process is already released
+  assert_eq!(
+    procfs::parse_peak_resident_size("Name:\tgame.exe\nState:\tZ (zombie)\n"),
+    None
+  );
+}
+
+#[test]
+fn test_parse_boot_time() {
+  let content = "cpu  1 2 3 4\nintr 0\nctxt 100\nbtime 1700000000\nprocesses 42\n";
+  assert_eq!(procfs::parse_boot_time(content), Some(1700000000));
+  assert_eq!(procfs::parse_boot_time("cpu 1 2 3"), None);
+  assert_eq!(
+    procfs::ticks_to_unix_millis(250, 1700000000, 100),
+    1700000002500
+  );
+}
+
+#[test]
+fn test_get_current_process() {
+  let pid = std::process::id();
+  assert!(platform::get_all_process_ppid().contains(&(pid, std::os::unix::process::parent_id())));
+
+  let entry = procfs::get_process_by_pid(pid).unwrap();
+  let exe = std::env::current_exe().unwrap();
+  assert_eq!(entry.path, exe.to_string_lossy());
+  assert_eq!(entry.ppid, std::os::unix::process::parent_id());
+  assert!(entry.start_time > 0);
+
+  let details = platform::get_process_details(pid);
+  assert!(details
+    .cmdline
+    .unwrap()
+    .starts_with(std::env::args().next().unwrap().as_str()));
+  assert_eq!(
+    details.cwd.unwrap(),
+    std::env::current_dir().unwrap().to_string_lossy()
+  );
+  assert_eq!(details.start_time, Some(entry.start_time));
+
+  let resources = platform::get_process_resources(pid).unwrap();
+  assert!(resources.peak_memory.unwrap() > 0);
+  assert!(resources.cpu_time.is_some());
+  assert!(resources.hard_faults.is_some());
+  // a process may always read its own I/O counters
+  assert!(resources.read_operations.unwrap() > 0);
+}
+
+#[test]
+fn test_join_cmdline() {
+  let args: Vec<String> = [
+    "wine",
+    r"C:\Program Files\Game\game.exe",
+    "",
+    r#"-name="a""#,
+  ]
+  .iter()
+  .map(|arg| arg.to_string())
+  .collect();
+  assert_eq!(
+    procfs::join_cmdline(&args),
+    r#"wine "C:\Program Files\Game\game.exe" "" "-name=\"a\"""#
+  );
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use crate::{
+  monitor::rules::{compile_glob, CompiledRule},
+  napi_monitor::MatchingRule,
+  utils::path::NormalizedPath,
+};
+
+fn rule(game_id: &str) -> MatchingRule {
+  MatchingRule {
+    game_id: game_id.to_string(),
+    exe_names: None,
+    path_prefixes: None,
+    cmdline_pattern: None,
+    parent_exe_names: None,
+    exclude_exe_names: None,
+    min_lifetime: None,
+  }
+}
+
+#[test]
+fn test_compile_glob() {
+  let re = compile_glob("Game*.exe").unwrap();
+  assert!(re.is_match("game.exe"));
+  assert!(re.is_match("GAME-x64.EXE"));
+  assert!(!re.is_match("game.exe.bak"));
+  assert!(!re.is_match("mygame.exe"));
+  assert!(compile_glob("game?.exe").unwrap().is_match("game2.exe"));
+  // regex meta characters are literal
+  assert!(!compile_glob("game(1).exe").unwrap().is_match("game1.exe"));
+  assert!(compile_glob("").is_err());
+  assert!(compile_glob("bin/game.exe").is_err());
+}
+
+#[test]
+fn test_compile_rule_errors() {
+  let errors = CompiledRule::compile(MatchingRule {
+    exe_names: Some(vec!["game.exe".to_string(), "".to_string()]),
+    path_prefixes: Some(vec![" ".to_string()]),
+    cmdline_pattern: Some("(".to_string()),
+    ..rule("game-id")
+  })
+  .err()
+  .unwrap();
+  let fields: Vec<_> = errors.iter().map(|e| e.field).collect();
+  assert_eq!(fields, vec!["exeNames", "pathPrefixes", "cmdlinePattern"]);
+  assert!(errors[0]
+    .to_string()
+    .starts_with("invalid exeNames of game game-id"));
+}
+
+#[test]
+fn test_rule_matching() {
+  let compiled = CompiledRule::compile(MatchingRule {
+    exe_names: Some(vec!["retroarch*".to_string()]),
+    path_prefixes: Some(vec!["/opt/game/".to_string()]),
+    cmdline_pattern: Some(r"--rom\s+\S*zelda".to_string()),
+    parent_exe_names: Some(vec!["launcher".to_string()]),
+    exclude_exe_names: Some(vec!["crashpad_handler".to_string()]),
+    min_lifetime: Some(3000),
+    ..rule("game-id")
+  })
+  .unwrap();
+  assert_eq!(compiled.game_id(), "game-id");
+  assert_eq!(compiled.min_lifetime(), Duration::from_secs(3));
+
+  assert!(compiled.identifies(&NormalizedPath::new("/usr/bin/retroarch")));
+  assert!(compiled.identifies(&NormalizedPath::new("/opt/game")));
+  assert!(compiled.identifies(&NormalizedPath::new("/opt/game/bin/game")));
+  assert!(!compiled.identifies(&NormalizedPath::new("/opt/gamedata/game")));
+  assert!(compiled.excludes(&NormalizedPath::new("/opt/game/crashpad_handler")));
+  assert!(!compiled.excludes(&NormalizedPath::new("/opt/game/bin/game")));
+
+  let parent = || vec![NormalizedPath::new("/usr/bin/launcher")];
+  let cmdline = |cmdline: &str| {
+    let cmdline = cmdline.to_string();
+    move || Some(cmdline)
+  };
+  assert!(compiled.accepts(cmdline("retroarch --rom /roms/zelda.z64"), parent));
+  assert!(!compiled.accepts(cmdline("retroarch --rom /roms/mario.z64"), parent));
+  assert!(!compiled.accepts(|| None, parent));
+  assert!(
+    !compiled.accepts(cmdline("retroarch --rom /roms/zelda.z64"), || vec![
+      NormalizedPath::new("/usr/bin/bash")
+    ])
+  );
+}
@@ -0,0 +1,156 @@
+use std::{collections::HashMap, fs};
+
+use crate::{
+  monitor::{gm::GameManager, known_games::KnownGameMatch},
+  tests::known_game,
+  utils::{
+    path::NormalizedPath,
+    steam::{self, AppManifest, SteamLibraries},
+  },
+};
+
+const APP_MANIFEST: &str = r#"
+"AppState"
+{
+	"appid"		"1245620"
+	"Universe"		"1"
+	"name"		"ELDEN RING"
+	"installdir"		"ELDEN RING"
+	"InstalledDepots"
+	{
+		"1245621"
+		{
+			"manifest"		"123"
+		}
+	}
+}
+"#;
+
+#[test]
+fn test_parse_app_id() {
+  assert_eq!(steam::parse_app_id_key("steam:570"), Some(570));
+  assert_eq!(steam::parse_app_id_key("STEAM:570"), Some(570));
+  assert_eq!(steam::parse_app_id_key("steam:0"), None);
+  assert_eq!(steam::parse_app_id_key(r"c:\games\steam:570"), None);
+  assert_eq!(steam::app_id_key(570), "steam:570");
+
+  let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+  assert_eq!(
+    steam::find_app_id_in_args(&args(&[
+      "/home/user/.steam/steam/ubuntu12_32/reaper",
+      "SteamLaunch",
+      "AppId=1245620",
+      "--",
+      "/opt/game",
+    ])),
+    Some(1245620)
+  );
+  // arguments of the game itself are ignored
+  assert_eq!(
+    steam::find_app_id_in_args(&args(&["reaper", "SteamLaunch", "--", "AppId=1"])),
+    None
+  );
+  assert_eq!(
+    steam::find_app_id_in_args(&args(&["game", "AppId=1"])),
+    None
+  );
+
+  let environ = HashMap::from([("SteamAppId".to_string(), "570".to_string())]);
+  assert_eq!(steam::find_app_id_in_environ(&environ), Some(570));
+  let environ = HashMap::from([("SteamAppId".to_string(), "0".to_string())]);
+  assert_eq!(steam::find_app_id_in_environ(&environ), None);
+}
+
+#[test]
+fn test_parse_manifest() {
+  assert_eq!(
+    steam::parse_app_manifest(APP_MANIFEST),
+    Some(AppManifest {
+      app_id: 1245620,
+      install_dir: "ELDEN RING".to_string(),
+    })
+  );
+  assert_eq!(
+    steam::parse_app_manifest(r#""AppState" { "appid" "1" }"#),
+    None
+  );
+
+  let folders = r#"
+"libraryfolders"
+{
+	"0"
+	{
+		"path"		"C:\\Program Files (x86)\\Steam"
+		"apps" { "228980" "1" }
+	}
+	"1"
+	{
+		"path"		"D:\\SteamLibrary"
+	}
+}
+"#;
+  assert_eq!(
+    steam::parse_library_folders(folders),
+    vec![
+      std::path::PathBuf::from(r"C:\Program Files (x86)\Steam"),
+      std::path::PathBuf::from(r"D:\SteamLibrary"),
+    ]
+  );
+}
+
+#[test]
+fn test_find_install_dir() {
+  let root = std::env::temp_dir().join(format!("vnite-steam-test-{}", std::process::id()));
+  let library = root.join("library");
+  fs::create_dir_all(root.join("steamapps")).unwrap();
+  fs::create_dir_all(library.join("steamapps")).unwrap();
+  fs::write(
+    root.join("steamapps").join("libraryfolders.vdf"),
+    format!(
+      "\"libraryfolders\" {{ \"0\" {{ \"path\" \"{}\" }} }}",
+      library.to_string_lossy().replace('\\', "\\\\")
+    ),
+  )
+  .unwrap();
+  fs::write(
+    library.join("steamapps").join("appmanifest_1245620.acf"),
+    APP_MANIFEST,
+  )
+  .unwrap();
+
+  let libraries = SteamLibraries::from_roots(vec![root.clone()]);
+  let install_dir = libraries.find_install_dir(1245620);
+  fs::remove_dir_all(&root).unwrap();
+
+  let expected = fs::canonicalize(std::env::temp_dir())
+    .unwrap()
+    .join(root.file_name().unwrap())
+    .join("library")
+    .join("steamapps")
+    .join("common")
+    .join("ELDEN RING");
+  assert_eq!(install_dir, Some(expected));
+  assert_eq!(libraries.find_install_dir(570), None);
+}
+
+#[test]
+fn test_match_steam_alias() {
+  let mut gm = GameManager::new();
+  gm.replace_known_games(vec![known_game("game-id", "steam:1245620")]);
+  let path = if cfg!(windows) {
+    r"c:\games\eldenring.exe"
+  } else {
+    "/games/eldenring"
+  };
+  assert_eq!(
+    gm.lookup_known_game(path.to_string(), vec![steam::app_id_key(1245620)]),
+    Some((
+      KnownGameMatch::Unique("game-id".to_string()),
+      NormalizedPath::new(path)
+    ))
+  );
+  assert_eq!(
+    gm.lookup_known_game(path.to_string(), vec![steam::app_id_key(570)]),
+    None
+  );
+}
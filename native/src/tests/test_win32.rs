@@ -1,38 +1,40 @@
-use crate::win32;
+use crate::{platform::Notification, win32};
 
 #[test]
 fn test_get_process_info() {
   let procs = win32::get_all_process();
   for proc in procs {
-    println!("full path: {}, pid: {}", proc.full_path, proc.pid)
+    println!("full path: {}, pid: {}", proc.path, proc.pid)
   }
 }
 
 #[test]
 fn test_send_notification() {
-  win32::send_notification(
-    "vnite".to_string(),
-    Some("Notification Title".to_string()),
-    Some("Line 1".to_string()),
-    Some("Line 2".to_string()),
-    None,
-    Some(false),
-  );
+  win32::send_notification(Notification {
+    app_id: "vnite".to_string(),
+    title: Some("Notification Title".to_string()),
+    line1: Some("Line 1".to_string()),
+    line2: Some("Line 2".to_string()),
+    image: None,
+    silent: Some(false),
+  })
+  .unwrap();
 
-  crate::win32::send_notification(
-    "vnite".to_string(),
-    Some("Notification Title2".to_string()),
-    Some("Line 1".to_string()),
-    Some("Line 2".to_string()),
-    Some(r"c:\\test.webp".to_string()),
-    Some(false),
-  );
+  crate::win32::send_notification(Notification {
+    app_id: "vnite".to_string(),
+    title: Some("Notification Title2".to_string()),
+    line1: Some("Line 1".to_string()),
+    line2: Some("Line 2".to_string()),
+    image: Some(r"c:\\test.webp".to_string()),
+    silent: Some(false),
+  })
+  .unwrap();
 }
 
 #[test]
 fn test_nt_path() {
-  let dos_path = win32::nt_to_dos_path(r"\Device\HarddiskVolume3\Program Files\rustup\rustc.exe");
-  if let Some(path) = dos_path {
+  let dos_paths = win32::nt_to_dos_paths(r"\Device\HarddiskVolume3\Program Files\rustup\rustc.exe");
+  for path in dos_paths {
     println!("DOS path: {}", path)
   }
 }
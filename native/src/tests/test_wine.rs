@@ -0,0 +1,155 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::{
+  monitor::{gm::GameManager, known_games::KnownGameMatch},
+  platform::{
+    linux::{procfs, wine},
+    ProcessImage,
+  },
+  tests::known_game,
+  utils::path::NormalizedPath,
+};
+
+fn drives() -> Vec<wine::DriveMapping> {
+  vec![
+    ('c', PathBuf::from("/home/user/.wine/drive_c")),
+    ('d', PathBuf::from("/mnt/games")),
+    ('z', PathBuf::from("/")),
+  ]
+}
+
+#[test]
+fn test_is_wine_loader() {
+  assert!(wine::is_wine_loader("/usr/bin/wine64-preloader"));
+  assert!(wine::is_wine_loader(
+    "/home/user/.steam/steam/steamapps/common/Proton 9.0/files/bin/wine"
+  ));
+  assert!(!wine::is_wine_loader("/usr/bin/winecfg"));
+  assert!(!wine::is_wine_loader("/mnt/games/wine.exe"));
+}
+
+#[test]
+fn test_path_translation() {
+  let drives = drives();
+  assert_eq!(
+    wine::windows_to_host_path(r"C:\Games\..\Game\game.exe", &drives),
+    Some(PathBuf::from("/home/user/.wine/drive_c/Game/game.exe"))
+  );
+  assert_eq!(
+    wine::windows_to_host_path(r"\\?\d:\Game\game.exe", &drives),
+    Some(PathBuf::from("/mnt/games/Game/game.exe"))
+  );
+  assert_eq!(wine::windows_to_host_path(r"E:\game.exe", &drives), None);
+  assert_eq!(wine::windows_to_host_path("game.exe", &drives), None);
+
+  // the most specific drive wins over `Z:`
+  assert_eq!(
+    wine::host_to_windows_path(&PathBuf::from("/mnt/games/Game/game.exe"), &drives),
+    Some(r"D:\Game\game.exe".to_string())
+  );
+  assert_eq!(
+    wine::host_to_windows_path(&PathBuf::from("/opt/game.exe"), &drives),
+    Some(r"Z:\opt\game.exe".to_string())
+  );
+}
+
+#[test]
+fn test_find_program_in_cmdline() {
+  let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+  assert_eq!(
+    wine::find_program_in_cmdline(&args(&[r"C:\Game\game.exe", "-windowed"])),
+    Some(r"C:\Game\game.exe")
+  );
+  assert_eq!(
+    wine::find_program_in_cmdline(&args(&["wine64-preloader", "wine64", "Game.EXE"])),
+    Some("Game.EXE")
+  );
+  // arguments of the program are never taken for the program
+  assert_eq!(
+    wine::find_program_in_cmdline(&args(&[
+      "wine64",
+      "/games/x/game.exe",
+      r"C:\saves\slot.ini"
+    ])),
+    Some("/games/x/game.exe")
+  );
+  assert_eq!(
+    wine::find_program_in_cmdline(&args(&["wine64-preloader", "wine64"])),
+    None
+  );
+  assert_eq!(
+    wine::find_program_in_cmdline(&args(&["wine64", "--version"])),
+    None
+  );
+}
+
+#[test]
+fn test_resolve_program() {
+  let drives = drives();
+  assert_eq!(
+    wine::resolve_program(r"D:\Game\game.exe", None, &drives),
+    Some(ProcessImage {
+      path: "/mnt/games/Game/game.exe".to_string(),
+      aliases: vec![r"D:\Game\game.exe".to_string()],
+    })
+  );
+  assert_eq!(
+    wine::resolve_program(
+      "bin/game.exe",
+      Some(&PathBuf::from("/mnt/games/Game")),
+      &drives
+    ),
+    Some(ProcessImage {
+      path: "/mnt/games/Game/bin/game.exe".to_string(),
+      aliases: vec![r"D:\Game\bin\game.exe".to_string()],
+    })
+  );
+  assert_eq!(wine::resolve_program("game.exe", None, &drives), None);
+}
+
+#[test]
+fn test_parse_environ() {
+  let environ = procfs::parse_environ(b"HOME=/home/user\0WINEPREFIX=/games/prefix\0EMPTY=\0");
+  assert_eq!(environ.get("EMPTY").map(String::as_str), Some(""));
+  assert_eq!(
+    wine::get_wine_prefix(&environ),
+    Some(PathBuf::from("/games/prefix"))
+  );
+
+  let environ = HashMap::from([("HOME".to_string(), "/home/user".to_string())]);
+  assert_eq!(
+    wine::get_wine_prefix(&environ),
+    Some(PathBuf::from("/home/user/.wine"))
+  );
+}
+
+#[test]
+fn test_match_windows_path_alias() {
+  let mut gm = GameManager::new();
+  gm.replace_known_games(vec![
+    known_game("wine-game", r"D:\Game"),
+    known_game("native-game", "/opt/native/game"),
+  ]);
+  assert_eq!(
+    gm.lookup_known_game(
+      "/mnt/games/Game/game.exe".to_string(),
+      vec![r"D:\Game\game.exe".to_string()]
+    ),
+    Some((
+      KnownGameMatch::Unique("wine-game".to_string()),
+      NormalizedPath::new(r"d:\game\game.exe")
+    ))
+  );
+  assert_eq!(
+    gm.lookup_known_game("/opt/native/game/run".to_string(), Vec::new()),
+    Some((
+      KnownGameMatch::Unique("native-game".to_string()),
+      NormalizedPath::new("/opt/native/game/run")
+    ))
+  );
+  // host paths are case sensitive
+  assert_eq!(
+    gm.lookup_known_game("/opt/Native/game/run".to_string(), Vec::new()),
+    None
+  );
+}
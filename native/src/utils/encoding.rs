@@ -0,0 +1,42 @@
+/// Code page of UTF-8, which a system can use as its ANSI code page since Windows 10 1903
+const CP_UTF8: u16 = 65001;
+
+/// Decode a string of a Windows code page, e.g. a `win:AnsiString` in the ANSI code page of the
+/// system, such as Shift-JIS (932) on Japanese Windows.
+///
+/// The string ends at the first NUL. Invalid sequences are replaced, unknown code pages are
+/// decoded as UTF-8 if they are valid, and as Windows-1252 otherwise.
+pub fn decode_ansi(bytes: &[u8], code_page: u16) -> String {
+  let bytes = until_nul(bytes, 1);
+  if bytes.is_ascii() {
+    return String::from_utf8_lossy(bytes).into_owned();
+  }
+  let encoding = match code_page {
+    CP_UTF8 => encoding_rs::UTF_8,
+    _ => match codepage::to_encoding(code_page) {
+      Some(encoding) => encoding,
+      None if std::str::from_utf8(bytes).is_ok() => encoding_rs::UTF_8,
+      None => encoding_rs::WINDOWS_1252,
+    },
+  };
+  encoding.decode_without_bom_handling(bytes).0.into_owned()
+}
+
+/// Decode a little endian UTF-16 string, e.g. a `win:UnicodeString`.
+///
+/// The string ends at the first NUL, unpaired surrogates and an odd trailing byte are replaced.
+pub fn decode_utf16le(bytes: &[u8]) -> String {
+  encoding_rs::UTF_16LE
+    .decode_without_bom_handling(until_nul(bytes, 2))
+    .0
+    .into_owned()
+}
+
+/// Cut a string at its NUL terminator of the given code unit size, if it has one
+fn until_nul(bytes: &[u8], unit_size: usize) -> &[u8] {
+  let len = bytes
+    .chunks(unit_size)
+    .position(|unit| unit.iter().all(|&b| b == 0))
+    .map_or(bytes.len(), |units| units * unit_size);
+  &bytes[..len]
+}
@@ -0,0 +1,310 @@
+use std::fmt;
+
+use crate::{platform::ProcessResources, utils::encoding};
+
+// Decoders of Microsoft-Windows-Kernel-Process events, they are kept apart from the ETW consumer so
+// that they work on plain bytes on any platform. The templates are documented by
+// `wevtutil gp Microsoft-Windows-Kernel-Process /ge /gm`.
+
+/// The user data of an event ended before a field of its template
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeError {
+  pub field: &'static str,
+  pub version: u8,
+}
+
+impl fmt::Display for DecodeError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "event data (version {}) is truncated at {}",
+      self.version, self.field
+    )
+  }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Token elevation of a started process
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elevation {
+  /// `TOKEN_ELEVATION_TYPE`, 1 default, 2 full (elevated) and 3 limited (filtered admin)
+  pub elevation_type: u32,
+  pub is_elevated: bool,
+}
+
+/// A process start event (`ProcessStartArgs` to `ProcessStartArgs_V4`).
+///
+/// Fields missing from older template versions are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessStart {
+  pub process_id: u32,
+  /// Since V2
+  pub sequence_number: Option<u64>,
+  /// Create time as a FILETIME
+  pub create_time: u64,
+  pub parent_process_id: u32,
+  /// Since V2
+  pub parent_sequence_number: Option<u64>,
+  pub session_id: u32,
+  /// Since V1
+  pub flags: Option<u32>,
+  /// Since V3
+  pub elevation: Option<Elevation>,
+  /// NT path of the process image, e.g. `\Device\HarddiskVolume3\Games\game.exe`
+  pub image_name: String,
+  /// Full name of the package of a packaged (UWP, MSIX) app, since V1
+  pub package_full_name: Option<String>,
+  /// Application id within the package, since V1
+  pub package_relative_app_id: Option<String>,
+  /// Since V4
+  pub security_mitigations: Option<u32>,
+}
+
+/// A process stop event (`ProcessStopArgs` to `ProcessStopArgs_V2`)
+///
+/// Fields missing from older template versions are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessStop {
+  pub process_id: u32,
+  /// Since V2
+  pub sequence_number: Option<u64>,
+  /// Create time as a FILETIME
+  pub create_time: u64,
+  /// Exit time as a FILETIME
+  pub exit_time: u64,
+  pub exit_code: u32,
+  pub token_elevation_type: u32,
+  pub handle_count: u32,
+  /// Commit charge in bytes when the process exited
+  pub commit_charge: u64,
+  pub commit_peak: u64,
+  /// Since V1
+  pub cpu_cycle_count: Option<u64>,
+  /// Since V1
+  pub read_operation_count: Option<u32>,
+  /// Since V1
+  pub write_operation_count: Option<u32>,
+  /// Since V1
+  pub read_transfer_kilobytes: Option<u32>,
+  /// Since V1
+  pub write_transfer_kilobytes: Option<u32>,
+  /// Since V1
+  pub hard_fault_count: Option<u32>,
+  /// Image file name without its directory, e.g. `game.exe`, decoded from the ANSI code page
+  pub image_name: String,
+}
+
+impl ProcessStop {
+  /// Resources the process consumed over its lifetime
+  pub fn resources(&self) -> ProcessResources {
+    ProcessResources {
+      peak_memory: Some(self.commit_peak),
+      cpu_cycles: self.cpu_cycle_count,
+      cpu_time: None,
+      read_operations: self.read_operation_count.map(Into::into),
+      write_operations: self.write_operation_count.map(Into::into),
+      read_bytes: self.read_transfer_kilobytes.map(|kb| u64::from(kb) * 1024),
+      write_bytes: self.write_transfer_kilobytes.map(|kb| u64::from(kb) * 1024),
+      hard_faults: self.hard_fault_count.map(Into::into),
+    }
+  }
+}
+
+/// Reads the fields of an event template in order, all integers are little endian
+struct FieldReader<'a> {
+  data: &'a [u8],
+  offset: usize,
+  version: u8,
+  /// Code page of ANSI strings
+  code_page: u16,
+}
+
+impl<'a> FieldReader<'a> {
+  fn new(data: &'a [u8], version: u8, code_page: u16) -> Self {
+    Self {
+      data,
+      offset: 0,
+      version,
+      code_page,
+    }
+  }
+
+  fn bytes(&mut self, field: &'static str, len: usize) -> Result<&'a [u8], DecodeError> {
+    let bytes = self
+      .data
+      .get(self.offset..self.offset + len)
+      .ok_or(DecodeError {
+        field,
+        version: self.version,
+      })?;
+    self.offset += len;
+    Ok(bytes)
+  }
+
+  fn u32(&mut self, field: &'static str) -> Result<u32, DecodeError> {
+    let bytes = self.bytes(field, 4)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap_or_default()))
+  }
+
+  fn u64(&mut self, field: &'static str) -> Result<u64, DecodeError> {
+    let bytes = self.bytes(field, 8)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap_or_default()))
+  }
+
+  /// The remaining data, a string has at least its terminator
+  fn rest(&self, field: &'static str) -> Result<&'a [u8], DecodeError> {
+    match &self.data[self.offset..] {
+      [] => Err(DecodeError {
+        field,
+        version: self.version,
+      }),
+      rest => Ok(rest),
+    }
+  }
+
+  /// Skip a SID, its length follows from the number of sub authorities in its header
+  fn skip_sid(&mut self, field: &'static str) -> Result<(), DecodeError> {
+    let header = self.bytes(field, 8)?;
+    let sub_authority_count = header[1] as usize;
+    self.bytes(field, sub_authority_count * 4)?;
+    Ok(())
+  }
+
+  /// Read a NUL terminated UTF-16 string, a missing terminator ends it with the data
+  fn unicode_string(&mut self, field: &'static str) -> Result<String, DecodeError> {
+    let rest = self.rest(field)?;
+    let len = rest
+      .chunks_exact(2)
+      .position(|unit| unit == [0, 0])
+      .map_or(rest.len(), |units| units * 2);
+    self.offset = (self.offset + len + 2).min(self.data.len());
+    Ok(encoding::decode_utf16le(&rest[..len]))
+  }
+
+  /// Read a NUL terminated string of the ANSI code page, a missing terminator ends it with the data
+  fn ansi_string(&mut self, field: &'static str) -> Result<String, DecodeError> {
+    let rest = self.rest(field)?;
+    let len = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+    self.offset = (self.offset + len + 1).min(self.data.len());
+    Ok(encoding::decode_ansi(&rest[..len], self.code_page))
+  }
+}
+
+/// Decode the user data of a process start event of the given template version.
+///
+/// Versions above V4 are decoded as V4, later versions only append fields.
+pub fn decode_process_start(version: u8, data: &[u8]) -> Result<ProcessStart, DecodeError> {
+  // the start template has no ANSI strings
+  let mut reader = FieldReader::new(data, version, 0);
+  let process_id = reader.u32("ProcessID")?;
+  let sequence_number = match version {
+    2.. => Some(reader.u64("ProcessSequenceNumber")?),
+    _ => None,
+  };
+  let create_time = reader.u64("CreateTime")?;
+  let parent_process_id = reader.u32("ParentProcessID")?;
+  let parent_sequence_number = match version {
+    2.. => Some(reader.u64("ParentProcessSequenceNumber")?),
+    _ => None,
+  };
+  let session_id = reader.u32("SessionID")?;
+  let flags = match version {
+    1.. => Some(reader.u32("Flags")?),
+    _ => None,
+  };
+  let elevation = match version {
+    3.. => {
+      let elevation_type = reader.u32("ProcessTokenElevationType")?;
+      let is_elevated = reader.u32("ProcessTokenIsElevated")? != 0;
+      reader.skip_sid("MandatoryLabel")?;
+      Some(Elevation {
+        elevation_type,
+        is_elevated,
+      })
+    }
+    _ => None,
+  };
+  let image_name = reader.unicode_string("ImageName")?;
+  let (package_full_name, package_relative_app_id) = match version {
+    1.. => {
+      let _image_checksum = reader.u32("ImageChecksum")?;
+      let _time_date_stamp = reader.u32("TimeDateStamp")?;
+      (
+        Some(reader.unicode_string("PackageFullName")?),
+        Some(reader.unicode_string("PackageRelativeAppId")?),
+      )
+    }
+    _ => (None, None),
+  };
+  let security_mitigations = match version {
+    4.. => Some(reader.u32("SecurityMitigations")?),
+    _ => None,
+  };
+  Ok(ProcessStart {
+    process_id,
+    sequence_number,
+    create_time,
+    parent_process_id,
+    parent_sequence_number,
+    session_id,
+    flags,
+    elevation,
+    image_name,
+    package_full_name,
+    package_relative_app_id,
+    security_mitigations,
+  })
+}
+
+/// Decode the user data of a process stop event of the given template version.
+///
+/// Versions above V2 are decoded as V2, later versions only append fields. The image name is
+/// decoded from the given code page, which is the ANSI code page of the system logging the event.
+pub fn decode_process_stop(
+  version: u8,
+  data: &[u8],
+  code_page: u16,
+) -> Result<ProcessStop, DecodeError> {
+  let mut reader = FieldReader::new(data, version, code_page);
+  let process_id = reader.u32("ProcessID")?;
+  let sequence_number = match version {
+    2.. => Some(reader.u64("ProcessSequenceNumber")?),
+    _ => None,
+  };
+  let create_time = reader.u64("CreateTime")?;
+  let exit_time = reader.u64("ExitTime")?;
+  let exit_code = reader.u32("ExitCode")?;
+  let token_elevation_type = reader.u32("TokenElevationType")?;
+  let handle_count = reader.u32("HandleCount")?;
+  let commit_charge = reader.u64("CommitCharge")?;
+  let commit_peak = reader.u64("CommitPeak")?;
+  let mut stop = ProcessStop {
+    process_id,
+    sequence_number,
+    create_time,
+    exit_time,
+    exit_code,
+    token_elevation_type,
+    handle_count,
+    commit_charge,
+    commit_peak,
+    cpu_cycle_count: None,
+    read_operation_count: None,
+    write_operation_count: None,
+    read_transfer_kilobytes: None,
+    write_transfer_kilobytes: None,
+    hard_fault_count: None,
+    image_name: String::new(),
+  };
+  if version >= 1 {
+    stop.cpu_cycle_count = Some(reader.u64("CPUCycleCount")?);
+    stop.read_operation_count = Some(reader.u32("ReadOperationCount")?);
+    stop.write_operation_count = Some(reader.u32("WriteOperationCount")?);
+    stop.read_transfer_kilobytes = Some(reader.u32("ReadTransferKiloBytes")?);
+    stop.write_transfer_kilobytes = Some(reader.u32("WriteTransferKiloBytes")?);
+    stop.hard_fault_count = Some(reader.u32("HardFaultCount")?);
+  }
+  stop.image_name = reader.ansi_string("ImageName")?;
+  Ok(stop)
+}
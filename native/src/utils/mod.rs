@@ -1,2 +1,8 @@
 pub mod types;
+pub mod encoding;
+// only consumed by the ETW monitor, tested on every platform
+#[cfg_attr(not(windows), allow(dead_code))]
+pub mod etw_event;
+pub mod nt_path;
 pub mod path;
+pub mod steam;
@@ -0,0 +1,111 @@
+/// NT device prefix of network paths, handled by the multiple UNC provider
+const MUP_DEVICE: &str = r"\Device\Mup";
+/// Legacy device of the SMB redirector, used by mapped network drives
+const LANMAN_DEVICE: &str = r"\Device\LanmanRedirector";
+
+/// Translation table from NT device paths (`\Device\HarddiskVolume3`) to DOS paths (`C:`).
+///
+/// Built from the targets of DOS devices as reported by `QueryDosDeviceW`, so that the table can
+/// be filled by hand where that is not available. Network paths are translated to UNC paths even
+/// without an entry.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub struct DriveMappings {
+  /// (canonical NT prefix, DOS prefix) pairs, the longest NT prefix first
+  mappings: Vec<(String, String)>,
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+impl DriveMappings {
+  /// Build the table from DOS devices and their targets, e.g. (`C:`, `\Device\HarddiskVolume3`).
+  ///
+  /// `subst` drives target another DOS path, e.g. (`S:`, `\??\C:\Games`), they are mapped to the
+  /// NT path of their target.
+  pub fn new(devices: impl IntoIterator<Item = (String, String)>) -> Self {
+    let devices: Vec<(String, String)> = devices.into_iter().collect();
+    let mut mappings: Vec<(String, String)> = devices
+      .iter()
+      .filter(|(_, target)| strip_dos_prefix(target).is_none())
+      .map(|(dos_device, target)| (canonicalize_device_path(target), dos_device.clone()))
+      .collect();
+    let substs: Vec<(String, String)> = devices
+      .iter()
+      .filter_map(|(dos_device, target)| {
+        let dos_target = strip_dos_prefix(target)?;
+        let nt_target = match dos_target.strip_prefix('\\') {
+          Some(unc) if unc.starts_with('\\') => format!("{MUP_DEVICE}{unc}"),
+          _ => mappings.iter().find_map(|(nt_prefix, dos_prefix)| {
+            let rest = strip_path_prefix(&dos_target, dos_prefix)?;
+            Some(format!("{nt_prefix}{rest}"))
+          })?,
+        };
+        Some((nt_target, dos_device.clone()))
+      })
+      .collect();
+    mappings.extend(substs);
+    mappings.retain(|(nt_prefix, _)| !nt_prefix.is_empty());
+    mappings.sort_by_key(|(nt_prefix, _)| std::cmp::Reverse(nt_prefix.len()));
+    Self { mappings }
+  }
+
+  /// Translate an NT path to all DOS paths it is reachable with, the most specific one first.
+  ///
+  /// A path below a `subst` drive is reachable through both drives, e.g. `S:\game.exe` and
+  /// `C:\Games\game.exe`. Returns an empty list if no device matches.
+  pub fn to_dos_paths(&self, nt_path: &str) -> Vec<String> {
+    if let Some(dos_path) = strip_dos_prefix(nt_path) {
+      return vec![dos_path];
+    }
+    let nt_path = canonicalize_device_path(nt_path);
+    let mut dos_paths: Vec<String> = self
+      .mappings
+      .iter()
+      .filter_map(|(nt_prefix, dos_prefix)| {
+        let rest = strip_path_prefix(&nt_path, nt_prefix)?;
+        Some(format!("{dos_prefix}{rest}"))
+      })
+      .collect();
+    // any share is reachable with its UNC path, whether or not it is mapped to a drive
+    if let Some(rest) = strip_path_prefix(&nt_path, MUP_DEVICE) {
+      dos_paths.push(format!(r"\{rest}"));
+    }
+    dos_paths
+  }
+}
+
+/// Strip the prefix of a path, comparing case-insensitively and whole components only
+fn strip_path_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+  let head = path.get(..prefix.len())?;
+  let rest = &path[prefix.len()..];
+  (head.eq_ignore_ascii_case(prefix) && (rest.is_empty() || rest.starts_with('\\'))).then_some(rest)
+}
+
+/// Strip the object manager prefix of a DOS path, `\??\C:\Games` is `C:\Games`
+/// and `\??\UNC\server\share` is `\\server\share`
+fn strip_dos_prefix(path: &str) -> Option<String> {
+  let rest = path.strip_prefix(r"\??\")?;
+  match strip_path_prefix(rest, "UNC") {
+    Some(share) => Some(format!(r"\{share}")),
+    None => Some(rest.to_string()),
+  }
+}
+
+/// Unify the devices of network paths to `\Device\Mup`.
+///
+/// Mapped network drives target a redirector path with a connection component,
+/// e.g. `\Device\LanmanRedirector\;Z:0000000000012345\server\share`, which is dropped, so that the
+/// drive matches the UNC path processes are started with.
+fn canonicalize_device_path(path: &str) -> String {
+  let Some(rest) =
+    strip_path_prefix(path, MUP_DEVICE).or_else(|| strip_path_prefix(path, LANMAN_DEVICE))
+  else {
+    return path.to_string();
+  };
+  rest
+    .split('\\')
+    .skip(1)
+    .skip_while(|component| component.starts_with(';'))
+    .fold(MUP_DEVICE.to_string(), |path, component| {
+      path + "\\" + component
+    })
+}
@@ -1,8 +1,192 @@
-pub fn normalize_os_path(mut path: String) -> String {
-  if cfg!(windows) {
-    path.make_ascii_lowercase();
-    path.trim_end_matches(['/', '\\']).replace('/', "\\")
-  } else {
-    path
+use std::{fmt, fs};
+
+/// A path normalized so that it can be used as a lookup key.
+///
+/// Windows paths are case insensitive and accept both separators. They are recognized by their
+/// drive letter or UNC prefix on other platforms as well, e.g. paths of Windows programs running
+/// in Wine. Other paths are kept case sensitive.
+///
+/// Normalization expands environment variables, strips `\\?\` long path prefixes, unifies and
+/// collapses separators and trims trailing separators. Symlinks are only resolved on request,
+/// see `resolve_symlinks`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NormalizedPath(String);
+
+impl NormalizedPath {
+  pub fn new(path: impl AsRef<str>) -> Self {
+    let path = expand_env_vars(path.as_ref());
+    let path = strip_long_path_prefix(&path);
+    if cfg!(windows) || is_windows_path(&path) {
+      Self(normalize_separators(&path.to_lowercase(), true))
+    } else {
+      Self(normalize_separators(&path, false))
+    }
   }
+
+  /// Resolve symlinks, junctions and relative components of an existing path.
+  ///
+  /// Returns `None` if the path does not exist or is not a path at all, e.g. an executable name.
+  pub fn resolve_symlinks(&self) -> Option<Self> {
+    if !self.is_path() {
+      return None;
+    }
+    let resolved = fs::canonicalize(&self.0).ok()?;
+    Some(Self::new(resolved.to_string_lossy()))
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+
+  pub fn into_string(self) -> String {
+    self.0
+  }
+
+  /// Whether this is a path rather than a bare name, e.g. an executable name
+  pub fn is_path(&self) -> bool {
+    self.0.contains(['\\', '/'])
+  }
+
+  /// Split into components, the root of an absolute path is kept as an empty first component,
+  /// so that `/games` and `games` are told apart
+  pub fn components(&self) -> impl Iterator<Item = &str> {
+    self
+      .0
+      .split(['\\', '/'])
+      .enumerate()
+      .filter(|(i, component)| *i == 0 || !component.is_empty())
+      .map(|(_, component)| component)
+  }
+
+  /// The last component, which is the executable name of a process path
+  pub fn file_name(&self) -> &str {
+    self.0.rsplit(['\\', '/']).next().unwrap_or(&self.0)
+  }
+
+  /// Whether this path is the directory itself or lives under it
+  pub fn is_under(&self, dir: &NormalizedPath) -> bool {
+    self.0.strip_prefix(&dir.0).is_some_and(|rest| {
+      rest.is_empty() || rest.starts_with(['\\', '/']) || dir.0.ends_with(['\\', '/'])
+    })
+  }
+}
+
+impl fmt::Display for NormalizedPath {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.0)
+  }
+}
+
+impl AsRef<str> for NormalizedPath {
+  fn as_ref(&self) -> &str {
+    &self.0
+  }
+}
+
+impl From<NormalizedPath> for String {
+  fn from(path: NormalizedPath) -> Self {
+    path.0
+  }
+}
+
+/// Check if a path starts with a drive letter or is a UNC path, e.g. `C:\` or `\\server\share`
+pub fn is_windows_path(path: &str) -> bool {
+  let bytes = path.as_bytes();
+  (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+    || path.starts_with(r"\\")
+}
+
+/// Strip `\\?\` (Win32 long path) and `\??\` (NT object namespace) prefixes
+fn strip_long_path_prefix(path: &str) -> std::borrow::Cow<'_, str> {
+  for prefix in [r"\\?\", r"\??\", "//?/"] {
+    let Some(rest) = path.strip_prefix(prefix) else {
+      continue;
+    };
+    // `\\?\UNC\server\share` is `\\server\share`
+    return match rest.get(..4) {
+      Some(unc) if unc.eq_ignore_ascii_case(r"UNC\") || unc.eq_ignore_ascii_case("UNC/") => {
+        format!(r"\\{}", &rest[4..]).into()
+      }
+      _ => rest.into(),
+    };
+  }
+  path.into()
+}
+
+/// Unify separators, collapse repeated ones except the leading `\\` of UNC paths,
+/// and trim trailing ones except the root
+fn normalize_separators(path: &str, windows: bool) -> String {
+  let is_separator = |c: char| c == '/' || (windows && c == '\\');
+  let separator = if windows { '\\' } else { '/' };
+  let mut normalized = String::with_capacity(path.len());
+  let mut root_len = 1;
+  let mut rest = path;
+  if windows && path.starts_with(is_separator) && path[1..].starts_with(is_separator) {
+    normalized.push_str(r"\\");
+    root_len = 2;
+    rest = path.trim_start_matches(is_separator);
+  }
+  for c in rest.chars() {
+    if !is_separator(c) {
+      normalized.push(c);
+    } else if !normalized.ends_with(separator) {
+      normalized.push(separator);
+    }
+  }
+  while normalized.len() > root_len && normalized.ends_with(separator) {
+    normalized.pop();
+  }
+  normalized
+}
+
+/// Expand `%VAR%` everywhere, plus `$VAR`, `${VAR}` and a leading `~` on platforms other than
+/// Windows. Undefined variables are kept as they are.
+fn expand_env_vars(path: &str) -> String {
+  let mut expanded = String::with_capacity(path.len());
+  let mut rest = path;
+  if !cfg!(windows) {
+    if let Some(after) = rest.strip_prefix('~') {
+      if after.is_empty() || after.starts_with('/') {
+        if let Ok(home) = std::env::var("HOME") {
+          expanded.push_str(&home);
+          rest = after;
+        }
+      }
+    }
+  }
+  while let Some(pos) = rest.find(|c| c == '%' || (c == '$' && !cfg!(windows))) {
+    expanded.push_str(&rest[..pos]);
+    let after = &rest[pos + 1..];
+    let (name, remainder) = match rest.as_bytes()[pos] {
+      b'%' => match after.find('%') {
+        Some(end) => (&after[..end], &after[end + 1..]),
+        None => ("", after),
+      },
+      _ => match after.strip_prefix('{') {
+        Some(braced) => match braced.find('}') {
+          Some(end) => (&braced[..end], &braced[end + 1..]),
+          None => ("", after),
+        },
+        None => {
+          let end = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+          (&after[..end], &after[end..])
+        }
+      },
+    };
+    match std::env::var(name).ok().filter(|_| !name.is_empty()) {
+      Some(value) => {
+        expanded.push_str(&value);
+        rest = remainder;
+      }
+      None => {
+        // keep the sigil and continue right after it
+        expanded.push_str(&rest[pos..pos + 1]);
+        rest = after;
+      }
+    }
+  }
+  expanded.push_str(rest);
+  expanded
 }
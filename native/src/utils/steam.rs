@@ -0,0 +1,176 @@
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+};
+
+/// Prefix of known game keys which refer to a Steam AppId rather than a path, e.g. `steam:570`
+pub const STEAM_KEY_PREFIX: &str = "steam:";
+
+/// Build the known game key of a Steam AppId
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn app_id_key(app_id: u32) -> String {
+  format!("{STEAM_KEY_PREFIX}{app_id}")
+}
+
+/// Parse a known game key such as `steam:570`, `None` if the key is a path
+pub fn parse_app_id_key(key: &str) -> Option<u32> {
+  let prefix = key.get(..STEAM_KEY_PREFIX.len())?;
+  if !prefix.eq_ignore_ascii_case(STEAM_KEY_PREFIX) {
+    return None;
+  }
+  parse_app_id(&key[STEAM_KEY_PREFIX.len()..])
+}
+
+/// AppId 0 is used by Steam for non-Steam shortcuts
+fn parse_app_id(value: &str) -> Option<u32> {
+  value.trim().parse().ok().filter(|id| *id != 0)
+}
+
+/// Find the AppId in the arguments of the Steam launch wrapper,
+/// e.g. `reaper SteamLaunch AppId=570 -- ~/.steam/.../game`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn find_app_id_in_args(args: &[String]) -> Option<u32> {
+  args
+    .iter()
+    .skip_while(|arg| *arg != "SteamLaunch")
+    .take_while(|arg| *arg != "--")
+    .find_map(|arg| arg.strip_prefix("AppId=").and_then(parse_app_id))
+}
+
+/// Find the AppId in the environment Steam sets up for the games it launches
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn find_app_id_in_environ(environ: &HashMap<String, String>) -> Option<u32> {
+  environ
+    .get("SteamAppId")
+    .and_then(|value| parse_app_id(value))
+}
+
+/// Collect all key-value pairs of a Valve KeyValues (VDF) document regardless of their nesting
+fn parse_vdf_pairs(content: &str) -> Vec<(String, String)> {
+  let mut pairs = Vec::new();
+  let mut key: Option<String> = None;
+  let mut chars = content.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '"' => {
+        let mut token = String::new();
+        while let Some(c) = chars.next() {
+          match c {
+            '"' => break,
+            '\\' => {
+              if let Some(escaped) = chars.next() {
+                token.push(escaped);
+              }
+            }
+            c => token.push(c),
+          }
+        }
+        match key.take() {
+          Some(k) => pairs.push((k, token)),
+          None => key = Some(token),
+        }
+      }
+      // the key was a section name
+      '{' | '}' => key = None,
+      _ => {}
+    }
+  }
+  pairs
+}
+
+/// Content of a `steamapps/appmanifest_<appid>.acf` file we are interested in
+#[derive(Debug, PartialEq)]
+pub struct AppManifest {
+  pub app_id: u32,
+  /// Directory name under `steamapps/common`
+  pub install_dir: String,
+}
+
+pub fn parse_app_manifest(content: &str) -> Option<AppManifest> {
+  let pairs = parse_vdf_pairs(content);
+  let get = |name: &str| {
+    pairs
+      .iter()
+      .find(|(k, _)| k.eq_ignore_ascii_case(name))
+      .map(|(_, v)| v.as_str())
+  };
+  Some(AppManifest {
+    app_id: parse_app_id(get("appid")?)?,
+    install_dir: get("installdir").filter(|v| !v.is_empty())?.to_string(),
+  })
+}
+
+/// Parse library paths from `steamapps/libraryfolders.vdf`
+pub fn parse_library_folders(content: &str) -> Vec<PathBuf> {
+  parse_vdf_pairs(content)
+    .into_iter()
+    .filter(|(k, _)| k.eq_ignore_ascii_case("path"))
+    .map(|(_, v)| PathBuf::from(v))
+    .collect()
+}
+
+/// Default Steam installation directories of current platform
+fn default_steam_roots() -> Vec<PathBuf> {
+  let mut roots = Vec::new();
+  if cfg!(windows) {
+    for var in ["ProgramFiles(x86)", "ProgramFiles"] {
+      if let Ok(dir) = std::env::var(var) {
+        roots.push(Path::new(&dir).join("Steam"));
+      }
+    }
+  } else if let Ok(home) = std::env::var("HOME") {
+    let home = Path::new(&home);
+    roots.push(home.join(".steam/steam"));
+    roots.push(home.join(".local/share/Steam"));
+    // Flatpak
+    roots.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+  }
+  roots
+}
+
+/// All Steam libraries found on this machine
+pub struct SteamLibraries {
+  libraries: Vec<PathBuf>,
+}
+
+impl SteamLibraries {
+  pub fn new(libraries: Vec<PathBuf>) -> Self {
+    Self { libraries }
+  }
+
+  /// Discover libraries from the default Steam installation directories
+  pub fn discover() -> Self {
+    Self::from_roots(default_steam_roots())
+  }
+
+  /// Discover libraries registered in `libraryfolders.vdf` of the given Steam installations
+  pub fn from_roots(roots: Vec<PathBuf>) -> Self {
+    let mut libraries: Vec<PathBuf> = Vec::new();
+    for root in roots {
+      // `~/.steam/steam` is usually a symlink to another root
+      let Ok(root) = fs::canonicalize(&root) else {
+        continue;
+      };
+      let folders = fs::read_to_string(root.join("steamapps").join("libraryfolders.vdf"))
+        .map(|content| parse_library_folders(&content))
+        .unwrap_or_default();
+      for library in std::iter::once(root).chain(folders) {
+        if !libraries.contains(&library) {
+          libraries.push(library);
+        }
+      }
+    }
+    Self::new(libraries)
+  }
+
+  /// Find the install directory of an app through its `appmanifest_<appid>.acf`
+  pub fn find_install_dir(&self, app_id: u32) -> Option<PathBuf> {
+    self.libraries.iter().find_map(|library| {
+      let steamapps = library.join("steamapps");
+      let content = fs::read_to_string(steamapps.join(format!("appmanifest_{app_id}.acf"))).ok()?;
+      let manifest = parse_app_manifest(&content).filter(|m| m.app_id == app_id)?;
+      Some(steamapps.join("common").join(manifest.install_dir))
+    })
+  }
+}
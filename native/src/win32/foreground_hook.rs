@@ -205,7 +205,7 @@ unsafe extern "system" fn win_foreground_callback(
 ///
 /// For regular desktop apps: Returns PID directly from window handle (1 API call)
 /// For UWP apps: Checks first visible child window with different PID (typically 1-6 iterations)
-/// 
+///
 /// This function is not used now but is retained here for potential future use.
 #[allow(dead_code)]
 unsafe fn get_actual_foreground_pid(hwnd: Foundation::HWND) -> u32 {
@@ -0,0 +1,113 @@
+use std::{ffi::c_void, mem};
+
+use windows::{
+  core::PCWSTR,
+  Win32::{
+    Foundation::ERROR_MORE_DATA,
+    System::{
+      Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+      },
+      JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectBasicProcessIdList,
+        QueryInformationJobObject,
+      },
+      Threading::{
+        self, OpenProcess, OpenThread, ResumeThread, PROCESS_SET_QUOTA, PROCESS_TERMINATE,
+        THREAD_SUSPEND_RESUME,
+      },
+    },
+  },
+};
+
+use super::process::HandleGuard;
+
+/// Creation flag of a process whose main thread does not run until it is resumed
+pub const CREATE_SUSPENDED: u32 = Threading::CREATE_SUSPENDED.0;
+
+// How many pids a single query lists, far more than a game runs
+const MAX_JOB_PROCESSES: usize = 512;
+
+/// `JOBOBJECT_BASIC_PROCESS_ID_LIST` with room for `MAX_JOB_PROCESSES` pids
+#[repr(C)]
+struct JobProcessIdList {
+  _assigned: u32,
+  listed: u32,
+  pids: [usize; MAX_JOB_PROCESSES],
+}
+
+/// An anonymous Job Object. Processes created by a process in the job are put in the job as well,
+/// since the job does not allow breaking away from it.
+///
+/// The processes keep running after the job is closed.
+pub struct JobObject(HandleGuard);
+
+// the handle is owned and only used for assignments and queries, which are safe from any thread
+unsafe impl Send for JobObject {}
+unsafe impl Sync for JobObject {}
+
+impl JobObject {
+  pub fn new() -> windows_core::Result<Self> {
+    let handle = unsafe { CreateJobObjectW(None, PCWSTR::null())? };
+    Ok(Self(HandleGuard::new(handle)))
+  }
+
+  /// Put a process in the job, the processes it created before are not
+  pub fn assign(&self, pid: u32) -> windows_core::Result<()> {
+    unsafe {
+      let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, false, pid)?;
+      let _process_guard = HandleGuard::new(process);
+      AssignProcessToJobObject(self.0 .0, process)
+    }
+  }
+
+  /// Pids of the processes in the job which are still running
+  pub fn pids(&self) -> Vec<u32> {
+    let mut list = JobProcessIdList {
+      _assigned: 0,
+      listed: 0,
+      pids: [0; MAX_JOB_PROCESSES],
+    };
+    let result = unsafe {
+      QueryInformationJobObject(
+        Some(self.0 .0),
+        JobObjectBasicProcessIdList,
+        &mut list as *mut JobProcessIdList as *mut c_void,
+        mem::size_of::<JobProcessIdList>() as u32,
+        None,
+      )
+    };
+    // the list is filled as far as it fits if there are more processes
+    if let Err(e) = result {
+      if e.code() != ERROR_MORE_DATA.to_hresult() {
+        return Vec::new();
+      }
+    }
+    let listed = (list.listed as usize).min(MAX_JOB_PROCESSES);
+    list.pids[..listed].iter().map(|pid| *pid as u32).collect()
+  }
+}
+
+/// Resume the threads of a process created suspended
+pub fn resume_process(pid: u32) -> windows_core::Result<()> {
+  unsafe {
+    let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0)?;
+    let _snapshot_guard = HandleGuard::new(snapshot);
+    let mut thread_entry = THREADENTRY32 {
+      dwSize: mem::size_of::<THREADENTRY32>() as u32,
+      ..Default::default()
+    };
+    // the snapshot lists the threads of all processes
+    Thread32First(snapshot, &mut thread_entry)?;
+    loop {
+      if thread_entry.th32OwnerProcessID == pid {
+        let thread = OpenThread(THREAD_SUSPEND_RESUME, false, thread_entry.th32ThreadID)?;
+        let _thread_guard = HandleGuard::new(thread);
+        ResumeThread(thread);
+      }
+      if Thread32Next(snapshot, &mut thread_entry).is_err() {
+        return Ok(());
+      }
+    }
+  }
+}
@@ -1,68 +1,78 @@
+mod foreground_hook;
+mod job;
 mod notification;
 mod nt_path;
 mod privilege;
 mod process;
 
-use crate::{log, napi_win32::ProcessInfo};
+use tokio::sync::mpsc;
 
-pub fn get_all_process() -> Vec<ProcessInfo> {
+use crate::platform::{Notification, ProcessDetails, ProcessEntry};
+
+pub use job::{JobObject, CREATE_SUSPENDED};
+pub use process::ProcessHandle;
+
+pub fn get_all_process() -> Vec<ProcessEntry> {
   process::get_all_process()
 }
 
-pub fn get_all_process_pid() -> Vec<u32> {
-  process::get_all_process_pid()
+pub fn get_all_process_ppid() -> Vec<(u32, u32)> {
+  process::get_all_process_ppid()
 }
 
 pub fn get_process_full_path_by_pid(pid: u32) -> String {
   process::get_process_full_path_by_pid(pid)
 }
 
-pub fn is_elevated_privilege() -> bool {
-  let result = privilege::is_elevated_privilege();
-  match result {
-    Err(e) => {
-      log::error(e.message().as_str());
-      false
-    }
-    Ok(v) => v,
-  }
+pub fn get_process_details(pid: u32) -> ProcessDetails {
+  process::get_process_details(pid)
+}
+
+pub fn get_process_start_time_by_pid(pid: u32) -> Option<i64> {
+  process::get_process_start_time_by_pid(pid)
+}
+
+/// Resume the threads of a process created suspended
+pub fn resume_process(pid: u32) -> windows_core::Result<()> {
+  job::resume_process(pid)
+}
+
+pub fn filetime_to_unix_millis(filetime: u64) -> i64 {
+  process::filetime_to_unix_millis(filetime)
+}
+
+pub fn is_elevated_privilege() -> windows_core::Result<bool> {
+  privilege::is_elevated_privilege()
 }
 
-pub fn nt_to_dos_path(nt_path: &str) -> Option<String> {
-  nt_path::nt_to_dos_path(nt_path)
+pub fn nt_to_dos_paths(nt_path: &str) -> Vec<String> {
+  nt_path::nt_to_dos_paths(nt_path)
 }
 
-pub fn send_notification(
-  app_id: String,
-  title: Option<String>,
-  line1: Option<String>,
-  line2: Option<String>,
-  image: Option<String>,
-  silent: Option<bool>,
-) {
-  let mut noti = notification::WinNotification::new(app_id);
-  if let Some(content) = title {
+pub fn send_notification(notification: Notification) -> windows_core::Result<()> {
+  let mut noti = notification::WinNotification::new(notification.app_id);
+  if let Some(content) = notification.title {
     noti.title(content);
   }
-  if let Some(content) = line1 {
+  if let Some(content) = notification.line1 {
     noti.line1(content);
   }
-  if let Some(content) = line2 {
+  if let Some(content) = notification.line2 {
     noti.line2(content);
   }
-  if let Some(content) = image {
+  if let Some(content) = notification.image {
     noti.image(content);
   }
-  if let Some(content) = silent {
+  if let Some(content) = notification.silent {
     noti.is_silent(content);
   }
-  let result = noti.show();
-  match result {
-    Ok(_) => {
-      log::info("A system notification has been sent.");
-    }
-    Err(err) => {
-      log::error(format!("Failed to send system notification: {}", err.message()).as_str());
-    }
-  }
+  noti.show()
+}
+
+pub fn install_foreground_hook(tx: mpsc::Sender<u32>) -> Result<(), String> {
+  foreground_hook::install_foreground_hook(tx)
+}
+
+pub fn uninstall_foreground_hook() {
+  foreground_hook::uninstall_foreground_hook()
 }
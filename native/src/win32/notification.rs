@@ -16,6 +16,7 @@ impl WinNotification {
   const POWERSHELL_APP_ID: &'static str =
     "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe";
 
+  #[allow(clippy::redundant_field_names)]
   pub fn new(app_id: String) -> Self {
     WinNotification {
       app_id: app_id,
@@ -1,21 +1,27 @@
-use std::collections::HashMap;
-use std::sync::OnceLock;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use windows::core::*;
 use windows::Win32::Storage::FileSystem::{GetLogicalDrives, QueryDosDeviceW};
 
-// Cache for NT device path to DOS drive letter mappings
-static DRIVE_MAPPINGS: OnceLock<HashMap<String, String>> = OnceLock::new();
-// refreshable mappings
-// static DRIVE_MAPPINGS: RwLock<Option<HashMap<String, String>>> = RwLock::new(None);
+use crate::{log, utils::nt_path::DriveMappings};
 
-/// Initialize the drive mappings cache
-fn init_drive_mappings() -> HashMap<String, String> {
-  let mut mappings = HashMap::new();
+/// A path of an unknown device queries the drives again at most once per interval,
+/// so that paths which never resolve (e.g. RAM disks without a drive letter) stay cheap
+const MISS_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
 
-  let drive_mask = unsafe { GetLogicalDrives() };
-  if drive_mask == 0 {
-    return mappings;
-  }
+struct DriveMappingsCache {
+  mappings: Arc<DriveMappings>,
+  /// Bitmask of `GetLogicalDrives` the mappings were queried with
+  drive_mask: u32,
+  refreshed_at: Instant,
+}
+
+// Cache for NT device path to DOS drive letter mappings, refreshed on mount changes
+static DRIVE_MAPPINGS: RwLock<Option<DriveMappingsCache>> = RwLock::new(None);
+
+/// Query the NT device path of every DOS drive
+fn query_drive_mappings(drive_mask: u32) -> DriveMappings {
+  let mut devices = Vec::new();
 
   // Iterate through all possible drive letters (A-Z)
   for i in 0..26 {
@@ -30,7 +36,8 @@ fn init_drive_mappings() -> HashMap<String, String> {
       .encode_utf16()
       .chain(std::iter::once(0))
       .collect();
-    let mut target_path = vec![0u16; 260];
+    // network drives target long redirector paths
+    let mut target_path = vec![0u16; 1024];
 
     let result = unsafe {
       QueryDosDeviceW(
@@ -43,6 +50,7 @@ fn init_drive_mappings() -> HashMap<String, String> {
       continue;
     }
 
+    // the first target is the current one
     let nt_device_path = {
       let len = target_path
         .iter()
@@ -51,33 +59,62 @@ fn init_drive_mappings() -> HashMap<String, String> {
       String::from_utf16_lossy(&target_path[..len])
     };
 
-    mappings.insert(nt_device_path, dos_device);
+    devices.push((dos_device, nt_device_path));
   }
 
-  mappings
+  DriveMappings::new(devices)
 }
 
-/// Converts an NT path to a DOS path using cached mappings
-/// This function is optimized for frequent calls
-pub fn nt_to_dos_path(nt_path: &str) -> Option<String> {
-  if nt_path.len() == 0 {
-    return None;
-  }
-  let mappings = DRIVE_MAPPINGS.get_or_init(init_drive_mappings);
-
-  // Find matching device path prefix
-  for (nt_device, dos_device) in mappings {
-    if nt_path.starts_with(nt_device) {
-      let remainder = &nt_path[nt_device.len()..];
-      return Some(format!("{}{}", dos_device, remainder));
+/// Get the cached mappings, querying them again if drives have been mounted or unmounted
+fn get_drive_mappings() -> Arc<DriveMappings> {
+  let drive_mask = unsafe { GetLogicalDrives() };
+  if let Ok(guard) = DRIVE_MAPPINGS.read() {
+    if let Some(cache) = guard
+      .as_ref()
+      .filter(|cache| cache.drive_mask == drive_mask)
+    {
+      return cache.mappings.clone();
     }
   }
+  refresh_drive_mappings_with(drive_mask)
+}
 
-  None
+fn refresh_drive_mappings_with(drive_mask: u32) -> Arc<DriveMappings> {
+  let mappings = Arc::new(query_drive_mappings(drive_mask));
+  if let Ok(mut guard) = DRIVE_MAPPINGS.write() {
+    *guard = Some(DriveMappingsCache {
+      mappings: mappings.clone(),
+      drive_mask,
+      refreshed_at: Instant::now(),
+    });
+  }
+  mappings
 }
 
-// Force refresh the drive mappings cache (useful if drives are mounted/unmounted)
-// pub fn refresh_drive_mappings() {
-//   let mut write_guard = DRIVE_MAPPINGS.write().unwrap();
-//   *write_guard = Some(init_drive_mappings());
-// }
+/// Converts an NT path to all DOS paths it is reachable with, the most specific one first.
+///
+/// Drives are queried again if their set changed since the last call, or if the path is on an
+/// unknown device, which happens when a drive is remounted to another volume.
+pub fn nt_to_dos_paths(nt_path: &str) -> Vec<String> {
+  if nt_path.is_empty() {
+    return Vec::new();
+  }
+  let dos_paths = get_drive_mappings().to_dos_paths(nt_path);
+  if !dos_paths.is_empty() {
+    return dos_paths;
+  }
+
+  let is_refreshable = DRIVE_MAPPINGS.read().is_ok_and(|guard| {
+    guard
+      .as_ref()
+      .is_none_or(|cache| cache.refreshed_at.elapsed() >= MISS_REFRESH_INTERVAL)
+  });
+  if !is_refreshable {
+    return dos_paths;
+  }
+  let dos_paths = refresh_drive_mappings_with(unsafe { GetLogicalDrives() }).to_dos_paths(nt_path);
+  if dos_paths.is_empty() {
+    log::info(format!("no DOS path is found for NT path {}", nt_path).as_str());
+  }
+  dos_paths
+}
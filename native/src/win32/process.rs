@@ -1,24 +1,41 @@
-use windows::Win32::{
-  Foundation::{CloseHandle, HANDLE},
-  System::{
-    Diagnostics::ToolHelp::{
-      CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
-      TH32CS_SNAPPROCESS,
-    },
-    Threading::{
-      OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
-      PROCESS_QUERY_LIMITED_INFORMATION,
+use std::{ffi::c_void, mem};
+
+use windows::{
+  Wdk::System::Threading::{
+    NtQueryInformationProcess, ProcessBasicInformation, ProcessCommandLineInformation,
+    PROCESSINFOCLASS,
+  },
+  Win32::{
+    Foundation::{CloseHandle, FILETIME, HANDLE, STILL_ACTIVE, UNICODE_STRING},
+    System::{
+      Diagnostics::{
+        Debug::ReadProcessMemory,
+        ToolHelp::{
+          CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+          TH32CS_SNAPPROCESS,
+        },
+      },
+      Threading::{
+        GetExitCodeProcess, GetProcessTimes, OpenProcess, QueryFullProcessImageNameW, PEB,
+        PROCESS_BASIC_INFORMATION, PROCESS_NAME_WIN32, PROCESS_QUERY_INFORMATION,
+        PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_VM_READ, RTL_USER_PROCESS_PARAMETERS,
+      },
     },
   },
 };
 
-use crate::napi_win32::ProcessInfo;
+use crate::platform::{ProcessDetails, ProcessEntry};
+
+// `RTL_USER_PROCESS_PARAMETERS.CurrentDirectory.DosPath` is not documented, it follows the console
+// handle, console flags and 3 std handles in `Reserved2`
+const CURRENT_DIRECTORY_OFFSET: usize =
+  mem::offset_of!(RTL_USER_PROCESS_PARAMETERS, Reserved2) + 5 * mem::size_of::<usize>();
 
 // RAII wrapper for Windows handles to ensure they're always closed
-struct HandleGuard(HANDLE);
+pub(super) struct HandleGuard(pub(super) HANDLE);
 
 impl HandleGuard {
-  fn new(handle: HANDLE) -> Self {
+  pub(super) fn new(handle: HANDLE) -> Self {
     HandleGuard(handle)
   }
 }
@@ -31,10 +48,10 @@ impl Drop for HandleGuard {
   }
 }
 
-/// Get all process infomation (pid, full path)
-pub fn get_all_process() -> Vec<ProcessInfo> {
+/// Get all process infomation (pid, ppid, full path, start time)
+pub fn get_all_process() -> Vec<ProcessEntry> {
   // Allocate a reasonable amount of memory in advance
-  let mut processes: Vec<ProcessInfo> = Vec::with_capacity(512);
+  let mut processes: Vec<ProcessEntry> = Vec::with_capacity(512);
 
   unsafe {
     // Create a snapshot of all running processes
@@ -57,11 +74,14 @@ pub fn get_all_process() -> Vec<ProcessInfo> {
       loop {
         let pid = proc_entry.th32ProcessID;
 
+        let mut start_time = 0i64;
+
         // Try to get the full path of the process
         let proc_full_path = match OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
           Ok(process_handle) => {
             // Use RAII guard to ensure handle is always closed
             let _handle_guard = HandleGuard::new(process_handle);
+            start_time = get_process_start_time(process_handle);
 
             // Use larger buffer to prevent truncation
             let mut buffer = [0u16; 512]; // Increased buffer size
@@ -89,9 +109,11 @@ pub fn get_all_process() -> Vec<ProcessInfo> {
           }
         };
 
-        processes.push(ProcessInfo {
+        processes.push(ProcessEntry {
           pid,
-          full_path: proc_full_path,
+          ppid: proc_entry.th32ParentProcessID,
+          path: proc_full_path,
+          start_time,
         });
 
         // Get the next process
@@ -105,9 +127,9 @@ pub fn get_all_process() -> Vec<ProcessInfo> {
   processes
 }
 
-/// Get all process (PID only)
-pub fn get_all_process_pid() -> Vec<u32> {
-  let mut processes: Vec<u32> = Vec::with_capacity(512);
+/// Get all process (PID and parent PID pairs)
+pub fn get_all_process_ppid() -> Vec<(u32, u32)> {
+  let mut processes: Vec<(u32, u32)> = Vec::with_capacity(512);
 
   unsafe {
     let snapshot = match CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) {
@@ -124,8 +146,7 @@ pub fn get_all_process_pid() -> Vec<u32> {
 
     if Process32FirstW(snapshot, &mut proc_entry).is_ok() {
       loop {
-        let pid = proc_entry.th32ProcessID;
-        processes.push(pid);
+        processes.push((proc_entry.th32ProcessID, proc_entry.th32ParentProcessID));
 
         // Get the next process
         if Process32NextW(snapshot, &mut proc_entry).is_err() {
@@ -165,6 +186,173 @@ pub fn get_process_full_path_by_pid(pid: u32) -> String {
   }
 }
 
+/// Get the start time of a process, `None` if the process is inaccessible
+pub fn get_process_start_time_by_pid(pid: u32) -> Option<i64> {
+  unsafe {
+    let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+    let _handle_guard = HandleGuard::new(handle);
+    Some(get_process_start_time(handle)).filter(|time| *time > 0)
+  }
+}
+
+/// An open handle of a process, Windows keeps the exit code of the process as long as it is open
+pub struct ProcessHandle(HandleGuard);
+
+// the handle is owned and only used for queries, which are safe from any thread
+unsafe impl Send for ProcessHandle {}
+unsafe impl Sync for ProcessHandle {}
+
+impl ProcessHandle {
+  /// Open a process to query it later, `None` if the process is inaccessible
+  pub fn open(pid: u32) -> Option<Self> {
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()? };
+    Some(Self(HandleGuard::new(handle)))
+  }
+
+  /// Exit code of the process, `None` while it is running
+  pub fn exit_code(&self) -> Option<i64> {
+    let mut exit_code = 0u32;
+    unsafe { GetExitCodeProcess(self.0 .0, &mut exit_code).ok()? };
+    // a process exiting with STILL_ACTIVE is indistinguishable from a running one
+    (exit_code != STILL_ACTIVE.0 as u32).then_some(exit_code.into())
+  }
+}
+
+/// Get the command line, working directory and start time of a process
+pub fn get_process_details(pid: u32) -> ProcessDetails {
+  let mut details = ProcessDetails::default();
+  unsafe {
+    if let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+      let _handle_guard = HandleGuard::new(handle);
+      details.start_time = Some(get_process_start_time(handle)).filter(|time| *time > 0);
+      details.cmdline = get_process_command_line(handle);
+    }
+    // reading the working directory requires access to the process memory
+    if let Ok(handle) = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid) {
+      let _handle_guard = HandleGuard::new(handle);
+      details.cwd = get_process_current_directory(handle);
+    }
+  }
+  details
+}
+
+/// Query a variable length information class of a process
+unsafe fn query_process_information(handle: HANDLE, class: PROCESSINFOCLASS) -> Option<Vec<u64>> {
+  let mut length = 0u32;
+  // the first call fails with STATUS_INFO_LENGTH_MISMATCH and reports the required length
+  let _ = NtQueryInformationProcess(handle, class, std::ptr::null_mut(), 0, &mut length);
+  if length == 0 {
+    return None;
+  }
+  // use u64 elements to keep the returned structures aligned
+  let mut buffer = vec![0u64; (length as usize).div_ceil(mem::size_of::<u64>())];
+  NtQueryInformationProcess(
+    handle,
+    class,
+    buffer.as_mut_ptr() as *mut c_void,
+    length,
+    &mut length,
+  )
+  .ok()
+  .ok()?;
+  Some(buffer)
+}
+
+/// Get the command line of a process, available since Windows 8.1
+unsafe fn get_process_command_line(handle: HANDLE) -> Option<String> {
+  let buffer = query_process_information(handle, ProcessCommandLineInformation)?;
+  // the buffer starts with a UNICODE_STRING pointing to the string which follows it
+  let command_line = &*(buffer.as_ptr() as *const UNICODE_STRING);
+  if command_line.Buffer.is_null() || command_line.Length == 0 {
+    return None;
+  }
+  let slice = std::slice::from_raw_parts(
+    command_line.Buffer.0,
+    command_line.Length as usize / mem::size_of::<u16>(),
+  );
+  Some(String::from_utf16_lossy(slice))
+}
+
+/// Read a value of type `T` from the memory of a process
+unsafe fn read_process_memory<T: Default>(handle: HANDLE, address: usize) -> Option<T> {
+  let mut value = T::default();
+  ReadProcessMemory(
+    handle,
+    address as *const c_void,
+    &mut value as *mut T as *mut c_void,
+    mem::size_of::<T>(),
+    None,
+  )
+  .ok()?;
+  Some(value)
+}
+
+/// Get the current directory of a process from its process parameters in PEB.
+///
+/// For a 32-bit process running under WOW64 this is the directory it was started in.
+unsafe fn get_process_current_directory(handle: HANDLE) -> Option<String> {
+  let mut info = PROCESS_BASIC_INFORMATION::default();
+  let mut length = 0u32;
+  NtQueryInformationProcess(
+    handle,
+    ProcessBasicInformation,
+    &mut info as *mut _ as *mut c_void,
+    mem::size_of::<PROCESS_BASIC_INFORMATION>() as u32,
+    &mut length,
+  )
+  .ok()
+  .ok()?;
+  if info.PebBaseAddress.is_null() {
+    return None;
+  }
+  let parameters: usize = read_process_memory(
+    handle,
+    info.PebBaseAddress as usize + mem::offset_of!(PEB, ProcessParameters),
+  )?;
+  let directory: UNICODE_STRING =
+    read_process_memory(handle, parameters + CURRENT_DIRECTORY_OFFSET)?;
+  if directory.Buffer.is_null() || directory.Length == 0 {
+    return None;
+  }
+  let mut buffer = vec![0u16; directory.Length as usize / mem::size_of::<u16>()];
+  ReadProcessMemory(
+    handle,
+    directory.Buffer.0 as *const c_void,
+    buffer.as_mut_ptr() as *mut c_void,
+    directory.Length as usize,
+    None,
+  )
+  .ok()?;
+  let directory = String::from_utf16_lossy(&buffer);
+  // the current directory always ends with a separator, except for the root of a drive
+  Some(match directory.strip_suffix('\\') {
+    Some(trimmed) if !trimmed.ends_with(':') => trimmed.to_string(),
+    _ => directory,
+  })
+}
+
+/// Get the creation time of a process in milliseconds since UNIX epoch, 0 on failure
+unsafe fn get_process_start_time(handle: HANDLE) -> i64 {
+  let mut creation = FILETIME::default();
+  let mut exit = FILETIME::default();
+  let mut kernel = FILETIME::default();
+  let mut user = FILETIME::default();
+  if GetProcessTimes(handle, &mut creation, &mut exit, &mut kernel, &mut user).is_err() {
+    return 0;
+  }
+  filetime_to_unix_millis(((creation.dwHighDateTime as u64) << 32) | creation.dwLowDateTime as u64)
+}
+
+/// Convert a FILETIME (100ns intervals since 1601-01-01) to milliseconds since UNIX epoch
+pub fn filetime_to_unix_millis(filetime: u64) -> i64 {
+  // 100ns intervals between 1601-01-01 and 1970-01-01
+  const EPOCH_DIFFERENCE: u64 = 116_444_736_000_000_000;
+  if filetime < EPOCH_DIFFERENCE {
+    return 0;
+  }
+  ((filetime - EPOCH_DIFFERENCE) / 10_000) as i64
+}
+
 // Helper function to safely extract process name from proc_entry
 fn get_process_name_fallback(proc_entry: &PROCESSENTRY32W) -> String {
   // Find the null terminator safely
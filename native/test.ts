@@ -3,10 +3,21 @@ import * as native from './dist'
 async function testMonitor(): Promise<void> {
   await native.startMonitoring(
     [
-      'c:\\program files\\windowsapps\\microsoft.windowscalculator_11.2502.2.0_x64__8wekyb3d8bbwe\\calculatorapp.exe',
-      'C:\\Program Files\\WindowsApps\\Microsoft.WindowsNotepad_11.2507.26.0_x64__8wekyb3d8bbwe\\Notepad\\Notepad.exe'
+      {
+        id: 'id1',
+        paths: [
+          'c:\\program files\\windowsapps\\microsoft.windowscalculator_11.2502.2.0_x64__8wekyb3d8bbwe\\calculatorapp.exe'
+        ],
+        matchMode: 'file' as native.KnownGameMatchMode
+      },
+      {
+        id: 'id2',
+        paths: [
+          'C:\\Program Files\\WindowsApps\\Microsoft.WindowsNotepad_11.2507.26.0_x64__8wekyb3d8bbwe\\Notepad\\Notepad.exe'
+        ],
+        matchMode: 'file' as native.KnownGameMatchMode
+      }
     ],
-    ['id1', 'id2'],
     (err: Error | null, arg: native.ProcessEvent) => {
       if (err) {
         console.error('err: ', err)
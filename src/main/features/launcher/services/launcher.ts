@@ -1,17 +1,26 @@
 import { spawn } from 'child_process'
 import { GameDBManager } from '~/core/database'
 import { shell } from 'electron'
-import { startPhantomMonitor } from '~/features/monitor'
+import { startPhantomMonitor, launchTrackedGame } from '~/features/monitor'
 
 export async function fileLauncher(gameId: string): Promise<void> {
   try {
     const fileConfig = await GameDBManager.getGameLocalValue(gameId, 'launcher.fileConfig')
 
-    const errorMessage = await shell.openPath(fileConfig.path)
-
-    if (errorMessage) {
-      console.error(`Failed to launch file for game ${gameId}:`, errorMessage)
-      throw new Error(errorMessage)
+    // executables are launched with their arguments and tracked from their start,
+    // other files and executables requiring elevation are opened through the shell,
+    // which shows the UAC prompt, and are found by the monitor through their paths
+    const isLaunched =
+      process.platform === 'win32' &&
+      /\.exe$/i.test(fileConfig.path) &&
+      (await launchTrackedGame(gameId, fileConfig.path, fileConfig.args ?? []))
+    if (!isLaunched) {
+      const errorMessage = await shell.openPath(fileConfig.path)
+
+      if (errorMessage) {
+        console.error(`Failed to launch file for game ${gameId}:`, errorMessage)
+        throw new Error(errorMessage)
+      }
     }
 
     // Startup Monitor
@@ -3,6 +3,7 @@ export {
   setupNativeMonitor,
   stopNativeMonitor,
   startPhantomMonitor,
+  launchTrackedGame,
   enableForegroundHook,
   disableForegroundHook,
   changeForegroundWaitTime
@@ -378,29 +378,15 @@ export class GameMonitor {
     })
   }
 
-  // Imitate monitoring behaviours to preserve compatibility.
-  // This function may be invoked multiple times in a single GameMonitor object if the
-  // monitoring mode is `folder` and there are more than one executables inside that folder
-  // are terminated.
-  // Return `true` if all the processes are stopped, `false` if there are still some processes
-  // keep running.
-  public async phantomStop(pid: number): Promise<boolean> {
-    const isAllExited = await this.mutex.runExclusive(() => {
-      const process = this.monitoredProcesses.find((process) => process.pid === pid)
-      if (process) {
+  // End the game once the native monitor reports that its last process has exited,
+  // regardless of which processes were seen by this monitor.
+  public async phantomEnd(): Promise<void> {
+    await this.mutex.runExclusive(() => {
+      for (const process of this.monitoredProcesses) {
         process.isRunning = false
       }
-      // if all monitored processes are stopped
-      if (
-        this.monitoredProcesses.length === 0 ||
-        this.monitoredProcesses.every((x) => !x.isRunning)
-      ) {
-        this.handleGameExit()
-        return true
-      }
-      return false
+      this.handleGameExit()
     })
-    return isAllExited
   }
 
   public async start(): Promise<void> {
@@ -17,15 +17,33 @@ const mutex: Mutex = new Mutex()
 // of enum values directly. This const mirrors `native.Process.ProcessEventType`.
 const ProcessEventType = {
   Creation: 0,
-  Termination: 1
+  Termination: 1,
+  SessionStarted: 2,
+  SessionEnded: 3,
+  AmbiguousMatch: 4
 }
 
-// Setup native monitor by giving it a full list of path-id pairs of local games.
-// A path can be either a folder or a file depends on `launcher.mode`.
+// Native match modes of the launcher monitor modes, mirrors `native.KnownGameMatchMode`
+const MatchModes: Record<string, native.KnownGameMatchMode> = {
+  file: 'file' as native.KnownGameMatchMode,
+  folder: 'folder-recursive' as native.KnownGameMatchMode,
+  process: 'exe-name' as native.KnownGameMatchMode
+}
+
+// Setup native monitor by giving it a full list of known games built from local games.
+// A path is matched as a file, a folder or an executable name depending on its monitor mode.
 // Normalization will be performed within the native module. We can simply hand over the path as it is.
 export async function setupNativeMonitor(): Promise<void> {
-  const [ids, pathes] = await getAllLocalGamesPair()
-  await native.startMonitoring(pathes, ids, processEventCallback)
+  const status = await native.startMonitoring(await getAllKnownGames(), processEventCallback)
+  for (const failure of status.failures) {
+    log.warn(`[Monitor] Native ${failure.backend} monitor is unavailable: ${failure.reason}`)
+  }
+  logKnownGameUpdate(status)
+  if (status.backend) {
+    log.info(`[Monitor] Native monitor is running with ${status.backend} backend`)
+  } else {
+    log.error('[Monitor] Failed to start native monitor, no backend is available')
+  }
 
   // Listen for game deletion to clean up monitor status
   // This prevents the monitor from tracking games that no longer exist
@@ -35,6 +53,28 @@ export async function setupNativeMonitor(): Promise<void> {
   })
 }
 
+// Launch an executable through the native module, so that its process is tracked as the game
+// from its start, whether or not its path matches the monitor path.
+// Returns `false` if the process can not be created directly, e.g. the game requires
+// administrator rights, in which case it has to be opened through the shell instead.
+export async function launchTrackedGame(
+  gameId: string,
+  path: string,
+  args: string[]
+): Promise<boolean> {
+  const result = await native.launchGame(gameId, { path, args, trackTree: true })
+  if (result.error) {
+    if (result.error.kind === 'elevation-required' || result.error.kind === 'spawn-failed') {
+      log.info(`[Monitor] Game ${gameId} is not launched directly: ${result.error.message}`)
+      return false
+    }
+    log.error(`[Monitor] Failed to launch game ${gameId}: ${result.error.message}`)
+    throw new Error(result.error.message)
+  }
+  log.info(`[Monitor] Game launched: ${gameId}, pid: ${result.pid}`)
+  return true
+}
+
 // Send a termination signal to native monitor.
 export async function stopNativeMonitor(): Promise<void> {
   await native.stopMonitoring()
@@ -54,17 +94,67 @@ export async function processEventCallback(
   switch (arg.eventType) {
     // a known game process is started...
     case ProcessEventType.Creation: {
+      if (arg.cmdline) {
+        log.info(
+          `[Monitor] Game process launched: ${gameId}, pid: ${arg.pid}, cmdline: ${arg.cmdline}, cwd: ${arg.cwd ?? '-'}`
+        )
+      }
       await startPhantomMonitor(gameId, arg.fullPath, arg.pid)
       break
     }
-    // a known game process is stopped...
-    case ProcessEventType.Termination: {
-      await stopPhantomMonitor(gameId, arg.pid)
+    // the last process of a known game is stopped, a game may consist of multiple processes
+    // so individual `Termination` events are not used to decide whether the game is stopped
+    case ProcessEventType.SessionEnded: {
+      log.info(
+        `[Monitor] Game session ended: ${gameId}, session: ${arg.session?.sessionId}, foreground time: ${arg.session?.foregroundTime}ms`
+      )
+      const resources = arg.session?.resources
+      if (resources) {
+        log.info(
+          `[Monitor] Game session resources: ${gameId}, peak memory: ${resources.peakMemory ?? '-'} bytes, read: ${resources.readBytes ?? '-'} bytes, written: ${resources.writeBytes ?? '-'} bytes`
+        )
+      }
+      // e.g. a missing locale or runtime makes a game exit right after launch
+      if (arg.crashed) {
+        log.warn(
+          `[Monitor] Game crashed right after launch: ${gameId}, pid: ${arg.pid}, exit code: ${arg.exitCode}`
+        )
+      }
+      await endPhantomMonitor(gameId)
+      break
+    }
+    // a process matches a monitor path shared by several games, it is not credited to any of them
+    case ProcessEventType.AmbiguousMatch: {
+      log.warn(
+        `[Monitor] Process ${arg.fullPath} (pid: ${arg.pid}) matches multiple games: ${arg.candidates?.join(', ')}`
+      )
       break
     }
   }
 }
 
+// Log the outcome of a known game update, the monitor status of the startup carries no reattributions
+function logKnownGameUpdate(update: {
+  errors: native.KnownGameError[]
+  conflicts: native.KnownGameConflict[]
+  reattributed?: native.KnownGameReattribution[]
+}): void {
+  for (const error of update.errors) {
+    log.warn(`[Monitor] Known game ${error.id} is rejected by native monitor: ${error.reason}`)
+  }
+  for (const conflict of update.conflicts) {
+    log.warn(
+      `[Monitor] Monitor path ${conflict.key} is shared by games: ${conflict.gameIds.join(', ')}`
+    )
+  }
+  // the native monitor has emitted the events of these processes already
+  for (const process of update.reattributed ?? []) {
+    log.info(
+      `[Monitor] Process ${process.pid} moved from game ${process.previousId ?? '-'} to ${process.id ?? '-'}`
+    )
+  }
+}
+
 export async function removeMonitorStub(gameId: string): Promise<void> {
   await mutex.runExclusive(() => {
     monitors.delete(gameId)
@@ -72,40 +162,36 @@ export async function removeMonitorStub(gameId: string): Promise<void> {
   await refreshTimerStatus()
 }
 
-// Update known game list
+// Update known game list, only the games which have changed are applied by the native monitor
 export async function updateKnownGames(): Promise<void> {
-  const [ids, pathes] = await getAllLocalGamesPair()
-  await native.replaceKnownGames(pathes, ids)
+  logKnownGameUpdate(await native.replaceKnownGames(await getAllKnownGames()))
 }
 
+// Adding a known game replaces the one with the same id, running processes are kept
 export async function updateKnownGame(gameId: string): Promise<void> {
-  await native.removeKnownGameById(gameId, true)
-
   const doc = await GameDBManager.getExistingGameLocal(gameId)
-  const pair = getLocalGamePair(doc)
-  if (!pair) {
+  const game = getKnownGame(doc)
+  if (!game) {
+    await native.removeKnownGameById(gameId, true)
     return
   }
 
-  await native.addKnownGame(pair[1], pair[0])
+  logKnownGameUpdate(await native.addKnownGame(game))
 }
 
-async function getAllLocalGamesPair(): Promise<[string[], string[]]> {
+async function getAllKnownGames(): Promise<native.KnownGame[]> {
   const allLocalGames = await GameDBManager.getAllGamesLocal()
-  const pathes: string[] = []
-  const ids: string[] = []
+  const games: native.KnownGame[] = []
   for (const doc of Object.values(allLocalGames)) {
-    const pair = getLocalGamePair(doc)
-    if (!pair) {
-      continue
+    const game = getKnownGame(doc)
+    if (game) {
+      games.push(game)
     }
-    ids.push(pair[0])
-    pathes.push(pair[1])
   }
-  return [ids, pathes]
+  return games
 }
 
-function getLocalGamePair(doc?: gameLocalDoc | null): [string, string] | undefined {
+function getKnownGame(doc?: gameLocalDoc | null): native.KnownGame | undefined {
   if (!doc) {
     log.warn('[Monitor] Detected an undefined local game doc, database may be corrupt')
     return undefined
@@ -117,29 +203,35 @@ function getLocalGamePair(doc?: gameLocalDoc | null): [string, string] | undefin
     )
     return undefined
   }
-  const monitorPath = doc.launcher[`${mode}Config`]?.monitorPath
+  const config = doc.launcher[`${mode}Config`]
+  const monitorPath =
+    config?.monitorPath || (mode === 'url' ? getSteamKey(doc.launcher.urlConfig?.url) : undefined)
   if (!monitorPath) {
     log.warn(
       `[Monitor] Detected an undefined monitorPath. Local doc id: ${doc._id}, mode: ${mode}, game path: ${doc?.path?.gamePath}`
     )
     return undefined
   }
-  return [doc._id, monitorPath]
+  return {
+    id: doc._id,
+    paths: [monitorPath],
+    // a Steam key is matched by its AppId and install directory
+    matchMode: config?.monitorPath ? MatchModes[config.monitorMode] : undefined
+  }
+}
+
+// Games launched through a Steam URL can be tracked by their AppId without a monitorPath.
+// The native monitor recognizes known game keys in the form of `steam:<appid>`.
+function getSteamKey(url?: string): string | undefined {
+  const match = url?.match(/^steam:\/\/(?:rungameid|run)\/(\d+)/)
+  return match ? `steam:${match[1]}` : undefined
 }
 
-async function stopPhantomMonitor(gameId: string, pid?: number): Promise<void> {
+async function endPhantomMonitor(gameId: string): Promise<void> {
   await mutex.runExclusive(async () => {
     const monitor = monitors.get(gameId)
-    if (!monitor) {
-      await refreshTimerStatus()
-      return
-    }
-    if (pid) {
-      if (await monitor.phantomStop(pid)) {
-        // if game was stopped, remove corresponding monitor from hash map
-        monitors.delete(gameId)
-      }
-    } else {
+    if (monitor) {
+      await monitor.phantomEnd()
       monitors.delete(gameId)
     }
     await refreshTimerStatus()
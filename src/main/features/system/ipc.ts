@@ -1,5 +1,6 @@
 import { generateUUID } from '@appUtils'
 import { app, OpenDialogOptions } from 'electron'
+import log from 'electron-log/main.js'
 import { ConfigDBManager } from '~/core/database'
 import { ipcManager } from '~/core/ipc'
 import {
@@ -260,7 +261,11 @@ export function setupSystemIPC(): void {
 
   ipcManager.on('system:change-foreground-timer', async (_, isEnabled: boolean) => {
     if (isEnabled) {
-      await enableForegroundHook()
+      try {
+        await enableForegroundHook()
+      } catch (err) {
+        log.error('failed to enable foreground hook', err)
+      }
     } else {
       await disableForegroundHook()
     }
@@ -256,14 +256,19 @@ async function captureToPersistenceLayer(
 
   const isSilent = !(await ConfigDBManager.getConfigValue('memory.enableNotificationSound'))
   // display a system notification
-  native.sendSystemNotification(
-    'vnite',
-    null,
-    i18next.t('system-notification:screenshotSaved'),
-    null,
-    imagePath,
-    isSilent
-  )
+  try {
+    native.sendSystemNotification(
+      'vnite',
+      null,
+      i18next.t('system-notification:screenshotSaved'),
+      null,
+      imagePath,
+      isSilent
+    )
+  } catch (error) {
+    // system notifications are not available on every platform
+    log.warn('[System] Failed to send screenshot notification: ', error)
+  }
 }
 
 async function captureGameMemory(